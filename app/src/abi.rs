@@ -0,0 +1,94 @@
+use serde::Serialize;
+
+// C ABI 导出清单, 供 script 校验 wasm 并生成 manifest
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AbiType {
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AbiExport {
+    pub name: String,
+    pub params: Vec<AbiType>,
+    pub results: Vec<AbiType>,
+    /// 返回值为结构体, 需要 multi-value 改写
+    #[serde(skip)]
+    pub multi_value: bool,
+}
+
+/// wasm32 上 `Data { len: usize, ptr: *mut u8 }` 的布局
+pub const DATA_RESULT: [AbiType; 2] = [AbiType::I32, AbiType::I32];
+
+const PTR_LEN: [AbiType; 2] = [AbiType::I32, AbiType::I32];
+
+impl AbiExport {
+    fn new(name: impl Into<String>, params: &[AbiType], results: &[AbiType]) -> Self {
+        AbiExport {
+            name: name.into(),
+            params: params.to_vec(),
+            results: results.to_vec(),
+            multi_value: false,
+        }
+    }
+
+    fn data(name: impl Into<String>) -> Self {
+        AbiExport {
+            multi_value: true,
+            ..Self::new(name, &PTR_LEN, &DATA_RESULT)
+        }
+    }
+}
+
+/// 各条目的 `parse_<name>` 与 `build_<name>` 导出, c_api 的实现与 [`c_exports`] 都由此展开
+macro_rules! c_entries {
+    ($m:ident) => {
+        $m!(User, SerializableUser, parse_user, build_user);
+        $m!(Summary, SerializableSummary, parse_summary, build_summary);
+        $m!(
+            GameRecord,
+            SerializableGameRecord,
+            parse_game_record,
+            build_game_record
+        );
+        $m!(
+            GameProgress,
+            SerializableGameProgress,
+            parse_game_progress,
+            build_game_progress
+        );
+        $m!(GameKey, SerializableGameKey, parse_game_key, build_game_key);
+        $m!(
+            Settings,
+            SerializableSettings,
+            parse_settings,
+            build_settings
+        );
+    };
+}
+#[cfg(feature = "c_abi")]
+pub(crate) use c_entries;
+
+/// c_api 中手写的导出, 与 c_api.rs 的对应关系由测试检查
+pub const C_FUNCTIONS: [&str; 4] = ["malloc", "free", "get_schema", "check_game_record"];
+
+pub fn c_exports() -> Vec<AbiExport> {
+    let mut exports = vec![
+        AbiExport::new(C_FUNCTIONS[0], &[AbiType::I32], &[AbiType::I32]),
+        AbiExport::new(C_FUNCTIONS[1], &PTR_LEN, &[]),
+        AbiExport::data(C_FUNCTIONS[2]),
+        AbiExport::data(C_FUNCTIONS[3]),
+    ];
+    macro_rules! push_entry {
+        ($struct_ty:ident, $serializable_ty:ident, $parse_fn:ident, $build_fn:ident) => {
+            exports.push(AbiExport::data(stringify!($parse_fn)));
+            exports.push(AbiExport::data(stringify!($build_fn)));
+        };
+    }
+    c_entries!(push_entry);
+    exports
+}
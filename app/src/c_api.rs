@@ -1,6 +1,7 @@
 use crate::game_key::{field::GameKey, serde::SerializableGameKey};
 use crate::game_progress::{field::GameProgress, serde::SerializableGameProgress};
 use crate::game_record::{field::GameRecord, serde::SerializableGameRecord};
+use crate::schema::entry_schema;
 use crate::settings::{field::Settings, serde::SerializableSettings};
 use crate::summary::{field::Summary, serde::SerializableSummary};
 use crate::user::{field::User, serde::SerializableUser};
@@ -43,7 +44,7 @@ pub extern "C" fn malloc(len: usize) -> *mut u8 {
             Err(_) => return std::ptr::null_mut(),
         };
 
-        return alloc(layout);
+        alloc(layout)
     }
 }
 
@@ -55,6 +56,44 @@ pub extern "C" fn free(ptr: *mut u8, len: usize) {
     }
 }
 
+// 返回 JSON 格式的 Schema, 参数为条目名
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn get_schema(name_ptr: *const u8, name_len: usize) -> Data {
+    if name_ptr.is_null() || name_len == 0 {
+        return empty_data();
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(name_ptr, name_len) };
+    let Some(schema) = std::str::from_utf8(bytes).ok().and_then(entry_schema) else {
+        return empty_data();
+    };
+
+    match serde_json::to_vec(&schema) {
+        Ok(v) => unsafe { malloc_data(v) },
+        Err(_) => empty_data(),
+    }
+}
+
+// 检查 gameRecord 中的异常谱面, 返回 msgpack 格式的列表
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn check_game_record(data_ptr: *const u8, data_len: usize) -> Data {
+    if data_ptr.is_null() || data_len == 0 {
+        return empty_data();
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(data_ptr, data_len) };
+    let bits = BitSlice::<u8, Lsb0>::from_slice(bytes);
+
+    let (record, _) = match GameRecord::parse(bits, &None) {
+        Ok(r) => r,
+        Err(_) => return empty_data(),
+    };
+    let anomalies = SerializableGameRecord::from(record).anomalies();
+
+    match rmp_serde::to_vec_named(&anomalies) {
+        Ok(v) => unsafe { malloc_data(v) },
+        Err(_) => empty_data(),
+    }
+}
+
 #[macro_export]
 macro_rules! impl_c_api {
     ($struct_ty:ty, $serializable_ty:ty, $parse_fn:ident, $build_fn:ident) => {
@@ -91,7 +130,11 @@ macro_rules! impl_c_api {
                 Err(_) => return empty_data(),
             };
 
-            let bitvec = match <$struct_ty>::from(serializable).build(&None) {
+            let item = match <$struct_ty>::try_from(serializable) {
+                Ok(v) => v,
+                Err(_) => return empty_data(),
+            };
+            let bitvec = match item.build(&None) {
                 Ok(v) => v,
                 Err(_) => return empty_data(),
             };
@@ -101,24 +144,4 @@ macro_rules! impl_c_api {
     };
 }
 
-impl_c_api!(User, SerializableUser, parse_user, build_user);
-impl_c_api!(Summary, SerializableSummary, parse_summary, build_summary);
-impl_c_api!(
-    GameRecord,
-    SerializableGameRecord,
-    parse_game_record,
-    build_game_record
-);
-impl_c_api!(
-    GameProgress,
-    SerializableGameProgress,
-    parse_game_progress,
-    build_game_progress
-);
-impl_c_api!(GameKey, SerializableGameKey, parse_game_key, build_game_key);
-impl_c_api!(
-    Settings,
-    SerializableSettings,
-    parse_settings,
-    build_settings
-);
+crate::abi::c_entries!(impl_c_api);
@@ -0,0 +1,139 @@
+use crate::game_record::difficulty::Difficulty;
+use crate::game_record::serde::SerializableGameRecord;
+use crate::game_record::song_id::SongId;
+use crate::game_version::GameVersion;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SongInfo {
+    pub id: SongId,
+    pub title: String,
+    pub composer: String,
+    pub illustrator: String,
+    pub chapter: String,
+    /// 难度 -> 谱师
+    #[serde(default)]
+    pub charters: BTreeMap<Difficulty, String>,
+}
+
+/// 曲目信息表, `game_version` 为收录到的最高 `Summary.game_version`
+///
+/// JSON 格式为 `{ "game_version": 110, "songs": [SongInfo, ...] }`,
+/// 需要内置时可配合 `include_str!` 使用 [`Catalog::from_tsv`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "CatalogFile", into = "CatalogFile")]
+pub struct Catalog {
+    pub game_version: GameVersion,
+    songs: BTreeMap<SongId, SongInfo>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CatalogFile {
+    game_version: GameVersion,
+    songs: Vec<SongInfo>,
+}
+
+impl From<CatalogFile> for Catalog {
+    fn from(file: CatalogFile) -> Self {
+        Catalog::from_songs(file.game_version, file.songs)
+    }
+}
+
+impl From<Catalog> for CatalogFile {
+    fn from(catalog: Catalog) -> Self {
+        CatalogFile {
+            game_version: catalog.game_version,
+            songs: catalog.songs.into_values().collect(),
+        }
+    }
+}
+
+impl Catalog {
+    pub fn from_songs(
+        game_version: GameVersion,
+        songs: impl IntoIterator<Item = SongInfo>,
+    ) -> Self {
+        Catalog {
+            game_version,
+            songs: songs.into_iter().map(|s| (s.id.clone(), s)).collect(),
+        }
+    }
+
+    /// 每行 `ID\t曲名\t曲师\t画师\t章节\tEZ谱师\tHD谱师\tIN谱师[\tAT谱师]`
+    pub fn from_tsv(game_version: GameVersion, text: &str) -> Result<Self, String> {
+        let mut songs = Vec::new();
+        for (line_no, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let cols: Vec<&str> = line.split('\t').map(str::trim).collect();
+            if cols.len() < 5 {
+                return Err(format!(
+                    "Catalog error: line {}: expected at least 5 columns",
+                    line_no + 1
+                ));
+            }
+            let id = cols[0]
+                .parse()
+                .map_err(|e| format!("Catalog error: line {}: {}", line_no + 1, e))?;
+            let charters = Difficulty::RATED
+                .into_iter()
+                .zip(cols[5..].iter())
+                .filter(|(_, charter)| !charter.is_empty())
+                .map(|(diff, charter)| (diff, charter.to_string()))
+                .collect();
+            songs.push(SongInfo {
+                id,
+                title: cols[1].to_string(),
+                composer: cols[2].to_string(),
+                illustrator: cols[3].to_string(),
+                chapter: cols[4].to_string(),
+                charters,
+            });
+        }
+        Ok(Catalog::from_songs(game_version, songs))
+    }
+
+    pub fn get(&self, id: &SongId) -> Option<&SongInfo> {
+        self.songs.get(id)
+    }
+
+    pub fn songs(&self) -> impl Iterator<Item = &SongInfo> {
+        self.songs.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.songs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.songs.is_empty()
+    }
+
+    /// 存档的游戏版本不高于本表时, 表中应包含存档里的全部曲目
+    pub fn covers(&self, game_version: GameVersion) -> bool {
+        game_version <= self.game_version
+    }
+
+    /// 表中有但记录里没有的曲目
+    pub fn missing_from(&self, record: &SerializableGameRecord) -> Vec<&SongInfo> {
+        self.songs
+            .values()
+            .filter(|info| record.get(&info.id).is_none())
+            .collect()
+    }
+
+    /// 记录里有但表中没有的键, 包括无法解析为 [`SongId`] 的键
+    pub fn unknown_in<'a>(&self, record: &'a SerializableGameRecord) -> Vec<&'a str> {
+        record
+            .0
+            .keys()
+            .filter(|name| {
+                name.parse::<SongId>()
+                    .map_or(true, |id| !self.songs.contains_key(&id))
+            })
+            .map(String::as_str)
+            .collect()
+    }
+}
@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// 课题模式等级, 百位为颜色, 余数为等级
+///
+/// 保留原始数值, 颜色越界时 [`ChallengeRank::tier`] 返回 None, 构建二进制时报错.
+/// 总是序列化为数字, 反序列化也接受 [`StructuredRank`] 的形式,
+/// 需要结构化输出时使用 [`ChallengeRank::structured`]
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(try_from = "ChallengeRankRepr", into = "u16")]
+pub struct ChallengeRank(pub u16);
+
+/// 形如 `{ "tier": "gold", "level": 48 }`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct StructuredRank {
+    pub tier: ChallengeTier,
+    #[cfg_attr(feature = "schema", schemars(range(max = 99)))]
+    pub level: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ChallengeTier {
+    None,
+    Green,
+    Blue,
+    Red,
+    Gold,
+    Rainbow,
+}
+
+impl ChallengeTier {
+    pub const ALL: [ChallengeTier; 6] = [
+        ChallengeTier::None,
+        ChallengeTier::Green,
+        ChallengeTier::Blue,
+        ChallengeTier::Red,
+        ChallengeTier::Gold,
+        ChallengeTier::Rainbow,
+    ];
+
+    pub fn from_index(index: u16) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    pub fn index(self) -> u16 {
+        self as u16
+    }
+}
+
+impl ChallengeRank {
+    pub fn new(tier: ChallengeTier, level: u8) -> Result<Self, String> {
+        if level >= 100 {
+            return Err(format!("ChallengeRank error: level {} out of range", level));
+        }
+        Ok(ChallengeRank(tier.index() * 100 + level as u16))
+    }
+
+    pub fn tier(self) -> Option<ChallengeTier> {
+        ChallengeTier::from_index(self.0 / 100)
+    }
+
+    pub fn level(self) -> u8 {
+        (self.0 % 100) as u8
+    }
+
+    /// 颜色越界时为 None
+    pub fn structured(self) -> Option<StructuredRank> {
+        Some(StructuredRank {
+            tier: self.tier()?,
+            level: self.level(),
+        })
+    }
+
+    pub fn validate(self) -> Result<(), String> {
+        match self.tier() {
+            Some(_) => Ok(()),
+            None => Err(format!(
+                "ChallengeRank error: tier {} out of range",
+                self.0 / 100
+            )),
+        }
+    }
+}
+
+impl From<u16> for ChallengeRank {
+    fn from(raw: u16) -> Self {
+        ChallengeRank(raw)
+    }
+}
+
+impl From<ChallengeRank> for u16 {
+    fn from(rank: ChallengeRank) -> Self {
+        rank.0
+    }
+}
+
+impl fmt::Display for ChallengeRank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.tier() {
+            Some(tier) => write!(f, "{:?} {}", tier, self.level()),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+enum ChallengeRankRepr {
+    Raw(#[cfg_attr(feature = "schema", schemars(range(max = 599)))] u16),
+    Structured(StructuredRank),
+}
+
+impl TryFrom<ChallengeRankRepr> for ChallengeRank {
+    type Error = String;
+
+    fn try_from(repr: ChallengeRankRepr) -> Result<Self, String> {
+        match repr {
+            ChallengeRankRepr::Raw(raw) => Ok(ChallengeRank(raw)),
+            ChallengeRankRepr::Structured(StructuredRank { tier, level }) => {
+                ChallengeRank::new(tier, level)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for ChallengeRank {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "ChallengeRank".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        ChallengeRankRepr::json_schema(generator)
+    }
+}
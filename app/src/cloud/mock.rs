@@ -0,0 +1,273 @@
+use super::{FileMeta, GameSave, LcDate, LcFile, LcPointer};
+use crate::save::upload::{checksum, iso_now};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+// 本地模拟 LeanCloud 与七牛上传接口, 仅用于离线测试
+
+#[derive(Default)]
+pub struct MockState {
+    pub session_token: String,
+    pub user_id: String,
+    pub saves: Vec<GameSave>,
+    pub files: HashMap<String, Vec<u8>>,
+    uploads: HashMap<String, PendingUpload>,
+    next_id: u64,
+}
+
+struct PendingUpload {
+    file_id: String,
+    data: Vec<u8>,
+}
+
+impl MockState {
+    fn new_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}{:08}", prefix, self.next_id)
+    }
+}
+
+pub struct MockServer {
+    pub base_url: String,
+    pub state: Arc<Mutex<MockState>>,
+    server: Arc<Server>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// 启动服务并放入一份存档, `archive` 为压缩包, `summary` 为 base64
+    pub fn start(session_token: &str, archive: Vec<u8>, summary: &str) -> Result<Self, String> {
+        let server = Arc::new(Server::http("127.0.0.1:0").map_err(|e| e.to_string())?);
+        let addr = server
+            .server_addr()
+            .to_ip()
+            .ok_or("mock server has no ip address")?;
+        let base_url = format!("http://{}", addr);
+
+        let mut state = MockState {
+            session_token: session_token.to_string(),
+            ..Default::default()
+        };
+        state.user_id = state.new_id("user");
+        let file_id = state.new_id("file");
+        let save_id = state.new_id("save");
+        let now = iso_now();
+        state.saves.push(GameSave {
+            object_id: save_id,
+            created_at: now.clone(),
+            updated_at: now.clone(),
+            modified_at: LcDate {
+                kind: "Date".to_string(),
+                iso: now,
+            },
+            summary: summary.to_string(),
+            game_file: LcFile {
+                object_id: file_id.clone(),
+                url: format!("{}/files/{}", base_url, file_id),
+                meta_data: Some(FileMeta {
+                    checksum: checksum(&archive),
+                    size: archive.len() as u64,
+                }),
+            },
+            user: LcPointer {
+                kind: "Pointer".to_string(),
+                class_name: "_User".to_string(),
+                object_id: state.user_id.clone(),
+            },
+        });
+        state.files.insert(file_id, archive);
+
+        let state = Arc::new(Mutex::new(state));
+        let handle = {
+            let server = server.clone();
+            let state = state.clone();
+            let base_url = base_url.clone();
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    let mut state = state.lock().unwrap();
+                    handle(&mut state, &base_url, request);
+                }
+            })
+        };
+
+        Ok(MockServer {
+            base_url,
+            state,
+            server,
+            handle: Some(handle),
+        })
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn header<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.as_str())
+}
+
+fn respond(request: Request, status: u16, body: Vec<u8>, content_type: &str) {
+    let header = Header::from_bytes("Content-Type", content_type).unwrap();
+    let _ = request.respond(
+        Response::from_data(body)
+            .with_status_code(status)
+            .with_header(header),
+    );
+}
+
+fn respond_json(request: Request, status: u16, body: Value) {
+    respond(
+        request,
+        status,
+        body.to_string().into_bytes(),
+        "application/json",
+    );
+}
+
+fn handle(state: &mut MockState, base_url: &str, mut request: Request) {
+    let mut body = Vec::new();
+    if request.as_reader().read_to_end(&mut body).is_err() {
+        return respond_json(request, 400, json!({ "error": "bad body" }));
+    }
+    let method = request.method().clone();
+    let url = request.url().to_string();
+    let path = url.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    // 文件下载与七牛上传不走 LeanCloud 鉴权
+    match (&method, segments.as_slice()) {
+        (Method::Get, ["files", id]) => {
+            return match state.files.get(*id) {
+                Some(data) => respond(request, 200, data.clone(), "application/octet-stream"),
+                None => respond_json(request, 404, json!({ "error": "file not found" })),
+            };
+        }
+        (_, ["qiniu", "buckets", _, "objects", _, "uploads", rest @ ..]) => {
+            let token = header(&request, "Authorization")
+                .and_then(|v| v.strip_prefix("UpToken "))
+                .unwrap_or_default()
+                .to_string();
+            let reply = qiniu(state, &token, &method, rest, &body);
+            return respond_json(request, if reply.is_null() { 400 } else { 200 }, reply);
+        }
+        _ => {}
+    }
+
+    if header(&request, "X-LC-Session") != Some(state.session_token.as_str()) {
+        return respond_json(
+            request,
+            401,
+            json!({ "code": 211, "error": "Could not find user." }),
+        );
+    }
+
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    let reply = match (&method, segments.as_slice()) {
+        (Method::Get, ["1.1", "users", "me"]) => {
+            Some(json!({ "objectId": state.user_id, "nickname": "mock" }))
+        }
+        (Method::Get, ["1.1", "classes", "_GameSave"]) => Some(json!({ "results": state.saves })),
+        (Method::Get, ["1.1", "classes", "_GameSave", id]) => state
+            .saves
+            .iter()
+            .find(|s| s.object_id == *id)
+            .map(|s| json!(s)),
+        (Method::Post, ["1.1", "fileTokens"]) => {
+            let file_id = state.new_id("file");
+            let token = state.new_id("token");
+            state.uploads.insert(
+                token.clone(),
+                PendingUpload {
+                    file_id: file_id.clone(),
+                    data: Vec::new(),
+                },
+            );
+            Some(json!({
+                "objectId": file_id,
+                "token": token,
+                "key": format!("gamesaves/{}/.save", file_id),
+                "bucket": "mock",
+                "upload_url": format!("{}/qiniu", base_url),
+                "provider": "qiniu",
+            }))
+        }
+        (Method::Post, ["1.1", "fileCallback"]) => {
+            let token = body["token"].as_str().unwrap_or_default();
+            state.uploads.remove(token).map(|upload| {
+                if body["result"].as_bool() == Some(true) {
+                    state.files.insert(upload.file_id, upload.data);
+                }
+                json!({})
+            })
+        }
+        (Method::Put, ["1.1", "classes", "_GameSave", id]) => {
+            update_save(state, base_url, id, &body)
+        }
+        (Method::Delete, ["1.1", "files", id]) => state.files.remove(*id).map(|_| json!({})),
+        _ => None,
+    };
+
+    match reply {
+        Some(reply) => respond_json(request, 200, reply),
+        None => respond_json(
+            request,
+            404,
+            json!({ "code": 101, "error": "Object not found." }),
+        ),
+    }
+}
+
+fn qiniu(state: &mut MockState, token: &str, method: &Method, rest: &[&str], body: &[u8]) -> Value {
+    // uploadId 直接使用上传凭证
+    let Some(upload) = state.uploads.get_mut(token) else {
+        return Value::Null;
+    };
+    match (method, rest) {
+        (Method::Post, []) => json!({ "uploadId": token }),
+        (Method::Put, [upload_id, "1"]) if *upload_id == token => {
+            upload.data = body.to_vec();
+            json!({ "etag": checksum(body) })
+        }
+        (Method::Post, [upload_id]) if *upload_id == token => {
+            json!({ "hash": checksum(&upload.data) })
+        }
+        _ => Value::Null,
+    }
+}
+
+fn update_save(state: &mut MockState, base_url: &str, id: &str, body: &Value) -> Option<Value> {
+    let file_id = body["gameFile"]["objectId"].as_str()?.to_string();
+    let data = state.files.get(&file_id)?;
+    let meta = FileMeta {
+        checksum: checksum(data),
+        size: data.len() as u64,
+    };
+    let save = state.saves.iter_mut().find(|s| s.object_id == id)?;
+
+    if let Some(summary) = body["summary"].as_str() {
+        save.summary = summary.to_string();
+    }
+    if let Some(iso) = body["modifiedAt"]["iso"].as_str() {
+        save.modified_at.iso = iso.to_string();
+    }
+    save.game_file = LcFile {
+        url: format!("{}/files/{}", base_url, file_id),
+        object_id: file_id,
+        meta_data: Some(meta),
+    };
+    save.updated_at = iso_now();
+    Some(json!({ "objectId": save.object_id, "updatedAt": save.updated_at }))
+}
@@ -0,0 +1,288 @@
+#[cfg(feature = "cloud-mock")]
+pub mod mock;
+
+use crate::save::PhiSave;
+use crate::save::archive::decode_summary;
+use crate::save::upload::{UploadPayload, checksum};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::io::Read;
+
+pub const BASE_URL: &str = "https://rak3ffdi.cloud.tds1.tapapis.cn";
+const APP_ID: &str = "rAK3FfdieFob2Nn8Am";
+const APP_KEY: &str = "Qr9AEqtuoSVS3zeD6iVbM4ZC0AtkJcQ89tywVyi0";
+const USER_AGENT: &str = "LeanCloud-CSharp-SDK/1.0.3";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LcDate {
+    #[serde(rename = "__type", default = "date_type")]
+    pub kind: String,
+    pub iso: String,
+}
+
+fn date_type() -> String {
+    "Date".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LcPointer {
+    #[serde(rename = "__type", default)]
+    pub kind: String,
+    #[serde(default)]
+    pub class_name: String,
+    pub object_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileMeta {
+    #[serde(rename = "_checksum", default)]
+    pub checksum: String,
+    #[serde(default)]
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LcFile {
+    pub object_id: String,
+    pub url: String,
+    #[serde(default)]
+    pub meta_data: Option<FileMeta>,
+}
+
+/// `_GameSave` 表中的一条记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameSave {
+    pub object_id: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub modified_at: LcDate,
+    pub summary: String,
+    pub game_file: LcFile,
+    pub user: LcPointer,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudUser {
+    pub object_id: String,
+    #[serde(default)]
+    pub nickname: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileToken {
+    object_id: String,
+    token: String,
+    key: String,
+    bucket: String,
+    #[serde(rename = "upload_url")]
+    upload_url: String,
+}
+
+/// [`CloudClient::upload`] 的结果
+#[derive(Debug, Clone)]
+pub struct Uploaded {
+    pub save: GameSave,
+    /// 不影响上传结果的问题, 如旧文件删除失败
+    pub warnings: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Results<T> {
+    results: Vec<T>,
+}
+
+pub struct CloudClient {
+    base_url: String,
+    session_token: String,
+    agent: ureq::Agent,
+}
+
+fn cloud_error(e: impl std::fmt::Display) -> String {
+    format!("Cloud error: {}", e)
+}
+
+fn read_json<T: DeserializeOwned>(
+    response: Result<ureq::Response, ureq::Error>,
+) -> Result<T, String> {
+    response
+        .map_err(cloud_error)?
+        .into_json()
+        .map_err(cloud_error)
+}
+
+impl CloudClient {
+    pub fn new(session_token: impl Into<String>) -> Self {
+        Self::with_base_url(BASE_URL, session_token)
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>, session_token: impl Into<String>) -> Self {
+        CloudClient {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            session_token: session_token.into(),
+            agent: ureq::AgentBuilder::new().user_agent(USER_AGENT).build(),
+        }
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        self.agent
+            .request(method, &format!("{}/1.1{}", self.base_url, path))
+            .set("X-LC-Id", APP_ID)
+            .set("X-LC-Key", APP_KEY)
+            .set("X-LC-Session", &self.session_token)
+            .set("Accept", "application/json")
+    }
+
+    fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<T, String> {
+        let request = self.request(method, path);
+        let response = match body {
+            Some(body) => request.send_json(body),
+            None => request.call(),
+        };
+        read_json(response)
+    }
+
+    pub fn me(&self) -> Result<CloudUser, String> {
+        self.call("GET", "/users/me", None)
+    }
+
+    pub fn list_saves(&self) -> Result<Vec<GameSave>, String> {
+        let results: Results<GameSave> = self.call("GET", "/classes/_GameSave", None)?;
+        Ok(results.results)
+    }
+
+    pub fn get_save(&self, object_id: &str) -> Result<GameSave, String> {
+        self.call("GET", &format!("/classes/_GameSave/{}", object_id), None)
+    }
+
+    pub fn download(&self, save: &GameSave) -> Result<Vec<u8>, String> {
+        let response = self
+            .agent
+            .get(&save.game_file.url)
+            .call()
+            .map_err(cloud_error)?;
+        let mut bytes = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut bytes)
+            .map_err(cloud_error)?;
+
+        if let Some(meta) = &save.game_file.meta_data
+            && !meta.checksum.is_empty()
+            && meta.checksum != checksum(&bytes)
+        {
+            return Err(cloud_error("checksum mismatch"));
+        }
+        Ok(bytes)
+    }
+
+    /// 下载并解析存档, summary 取自云端记录
+    pub fn fetch(&self, save: &GameSave) -> Result<PhiSave, String> {
+        let mut phi_save = PhiSave::from_archive(&self.download(save)?)?;
+        phi_save.summary = Some(decode_summary(&save.summary)?);
+        Ok(phi_save)
+    }
+
+    /// 上传 [`PhiSave::prepare_upload`] 生成的存档并更新记录, 之后尽量删除旧文件
+    pub fn upload(&self, save: &GameSave, payload: &UploadPayload) -> Result<Uploaded, String> {
+        let token: FileToken = self.call(
+            "POST",
+            "/fileTokens",
+            Some(json!({
+                "name": ".save",
+                "__type": "File",
+                "ACL": { save.user.object_id.clone(): { "read": true, "write": true } },
+                "prefix": "gamesaves",
+                "metaData": {
+                    "size": payload.archive.len(),
+                    "_checksum": payload.checksum,
+                    "prefix": "gamesaves",
+                },
+            })),
+        )?;
+
+        let result = self.upload_qiniu(&token, &payload.archive);
+        let _: Value = self.call(
+            "POST",
+            "/fileCallback",
+            Some(json!({ "result": result.is_ok(), "token": token.token })),
+        )?;
+        result?;
+
+        let _: Value = self.call(
+            "PUT",
+            &format!("/classes/_GameSave/{}", save.object_id),
+            Some(json!({
+                "summary": payload.summary,
+                "modifiedAt": { "__type": "Date", "iso": payload.modified_at },
+                "gameFile": { "__type": "Pointer", "className": "_File", "objectId": token.object_id },
+                "user": { "__type": "Pointer", "className": "_User", "objectId": save.user.object_id },
+            })),
+        )?;
+
+        // 记录已指向新文件, 旧文件删除失败只作为警告
+        let mut warnings = Vec::new();
+        let deleted: Result<Value, String> = self.call(
+            "DELETE",
+            &format!("/files/{}", save.game_file.object_id),
+            None,
+        );
+        if let Err(e) = deleted {
+            warnings.push(format!(
+                "failed to delete old file {}: {}",
+                save.game_file.object_id, e
+            ));
+        }
+
+        Ok(Uploaded {
+            save: self.get_save(&save.object_id)?,
+            warnings,
+        })
+    }
+
+    fn upload_qiniu(&self, token: &FileToken, archive: &[u8]) -> Result<(), String> {
+        let url = format!(
+            "{}/buckets/{}/objects/{}/uploads",
+            token.upload_url.trim_end_matches('/'),
+            token.bucket,
+            URL_SAFE.encode(&token.key)
+        );
+        let auth = format!("UpToken {}", token.token);
+
+        let init = self.agent.post(&url).set("Authorization", &auth).call();
+        let init: Value = read_json(init)?;
+        let upload_id = init["uploadId"]
+            .as_str()
+            .ok_or_else(|| cloud_error("missing uploadId"))?;
+
+        let part = self
+            .agent
+            .put(&format!("{}/{}/1", url, upload_id))
+            .set("Authorization", &auth)
+            .set("Content-Type", "application/octet-stream")
+            .send_bytes(archive);
+        let part: Value = read_json(part)?;
+        let etag = part["etag"]
+            .as_str()
+            .ok_or_else(|| cloud_error("missing etag"))?;
+
+        self.agent
+            .post(&format!("{}/{}", url, upload_id))
+            .set("Authorization", &auth)
+            .send_json(json!({ "parts": [{ "partNumber": 1, "etag": etag }] }))
+            .map_err(cloud_error)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,420 @@
+use crate::game_key::{field::GameKey, serde::*};
+use crate::game_progress::data::Data;
+use crate::game_progress::{field::GameProgress, serde::*};
+use crate::game_record::difficulty::Difficulty;
+use crate::game_record::{field::GameRecord, serde::*};
+use crate::settings::{field::Settings, serde::*, value::*};
+use crate::summary::{field::Summary, serde::*};
+use crate::user::{field::User, serde::SerializableUser};
+use bitvec::prelude::*;
+use shua_struct::field::BinaryField;
+use std::collections::BTreeMap;
+
+wit_bindgen::generate!({
+    path: "wit",
+    world: "phi-save-codec",
+});
+
+use exports::shua::phi_save_codec::codec::{self as wit, CodecError};
+
+struct Component;
+
+export!(Component);
+
+impl From<std::convert::Infallible> for CodecError {
+    fn from(e: std::convert::Infallible) -> Self {
+        match e {}
+    }
+}
+
+fn fixed<const N: usize>(name: &str, v: Vec<bool>) -> Result<[bool; N], CodecError> {
+    v.try_into().map_err(|v: Vec<bool>| {
+        CodecError::Invalid(format!("{} expects {} flags, got {}", name, N, v.len()))
+    })
+}
+
+impl From<wit::Difficulty> for Difficulty {
+    fn from(d: wit::Difficulty) -> Self {
+        match d {
+            wit::Difficulty::Ez => Difficulty::Ez,
+            wit::Difficulty::Hd => Difficulty::Hd,
+            wit::Difficulty::In => Difficulty::In,
+            wit::Difficulty::At => Difficulty::At,
+            wit::Difficulty::Legacy => Difficulty::Legacy,
+        }
+    }
+}
+
+impl From<Difficulty> for wit::Difficulty {
+    fn from(d: Difficulty) -> Self {
+        match d {
+            Difficulty::Ez => wit::Difficulty::Ez,
+            Difficulty::Hd => wit::Difficulty::Hd,
+            Difficulty::In => wit::Difficulty::In,
+            Difficulty::At => wit::Difficulty::At,
+            Difficulty::Legacy => wit::Difficulty::Legacy,
+        }
+    }
+}
+
+// user
+impl From<SerializableUser> for wit::User {
+    fn from(u: SerializableUser) -> Self {
+        Self {
+            show_player_id: u.show_player_id,
+            self_intro: u.self_intro,
+            avatar: u.avatar,
+            background: u.background,
+        }
+    }
+}
+
+impl TryFrom<wit::User> for SerializableUser {
+    type Error = CodecError;
+    fn try_from(u: wit::User) -> Result<Self, CodecError> {
+        Ok(Self {
+            show_player_id: u.show_player_id,
+            self_intro: u.self_intro,
+            avatar: u.avatar,
+            background: u.background,
+        })
+    }
+}
+
+// summary
+impl From<SerializableLevel> for wit::Level {
+    fn from(l: SerializableLevel) -> Self {
+        Self {
+            clear: l.clear,
+            fc: l.fc,
+            phi: l.phi,
+        }
+    }
+}
+
+impl From<wit::Level> for SerializableLevel {
+    fn from(l: wit::Level) -> Self {
+        Self {
+            clear: l.clear,
+            fc: l.fc,
+            phi: l.phi,
+        }
+    }
+}
+
+impl From<SerializableSummary> for wit::Summary {
+    fn from(s: SerializableSummary) -> Self {
+        Self {
+            save_version: s.save_version,
+            challenge_mode_rank: s.challenge_mode_rank.into(),
+            rks: s.rks,
+            game_version: s.game_version.into(),
+            avatar: s.avatar,
+            level: wit::MultiLevel {
+                ez: s.level.ez.into(),
+                hd: s.level.hd.into(),
+                in_: s.level.r#in.into(),
+                at: s.level.at.into(),
+            },
+        }
+    }
+}
+
+impl TryFrom<wit::Summary> for SerializableSummary {
+    type Error = CodecError;
+    fn try_from(s: wit::Summary) -> Result<Self, CodecError> {
+        Ok(Self {
+            save_version: s.save_version,
+            challenge_mode_rank: s.challenge_mode_rank.into(),
+            rks: s.rks,
+            game_version: s.game_version.into(),
+            avatar: s.avatar,
+            level: SerializableMultiLevel {
+                ez: s.level.ez.into(),
+                hd: s.level.hd.into(),
+                r#in: s.level.in_.into(),
+                at: s.level.at.into(),
+            },
+        })
+    }
+}
+
+// game_record
+impl From<SerializableGameRecord> for wit::GameRecord {
+    fn from(r: SerializableGameRecord) -> Self {
+        let songs =
+            r.0.into_iter()
+                .map(|(name, song)| wit::SongRecord {
+                    name,
+                    levels: song
+                        .into_iter()
+                        .map(|(diff, rec)| wit::LevelRecord {
+                            difficulty: diff.into(),
+                            score: rec.score,
+                            acc: rec.acc,
+                            fc: rec.fc,
+                        })
+                        .collect(),
+                })
+                .collect();
+        Self { songs }
+    }
+}
+
+impl TryFrom<wit::GameRecord> for SerializableGameRecord {
+    type Error = CodecError;
+    fn try_from(r: wit::GameRecord) -> Result<Self, CodecError> {
+        let mut map = BTreeMap::new();
+        for song in r.songs {
+            let mut song_map = BTreeMap::new();
+            for level in song.levels {
+                let diff = Difficulty::from(level.difficulty);
+                let rec = SerializableLevelRecord {
+                    score: level.score,
+                    acc: level.acc,
+                    fc: level.fc,
+                };
+                if song_map.insert(diff, rec).is_some() {
+                    return Err(CodecError::Invalid(format!(
+                        "duplicate difficulty {} for {}",
+                        diff, song.name
+                    )));
+                }
+            }
+            map.insert(song.name, song_map);
+        }
+        Ok(SerializableGameRecord(map))
+    }
+}
+
+// game_progress
+impl TryFrom<SerializableGameProgress> for wit::GameProgress {
+    type Error = CodecError;
+    fn try_from(g: SerializableGameProgress) -> Result<Self, CodecError> {
+        let [kib, mib, gib, tib, pib] = g.money.units().map_err(CodecError::Parse)?;
+        Ok(Self {
+            base: wit::ProgressBase {
+                is_first_run: g.base.is_first_run,
+                legacy_chapter_finished: g.base.legacy_chapter_finished,
+                already_show_collection_tip: g.base.already_show_collection_tip,
+                already_show_auto_unlock_in_tip: g.base.already_show_auto_unlock_in_tip,
+            },
+            completed: g.completed,
+            song_update_info: g.song_update_info,
+            challenge_mode_rank: g.challenge_mode_rank.into(),
+            money: wit::Money {
+                kib,
+                mib,
+                gib,
+                tib,
+                pib,
+            },
+            unlock_flag_of_spasmodic: g.unlock_flag_of_spasmodic.bits.to_vec(),
+            unlock_flag_of_igallta: g.unlock_flag_of_igallta.bits.to_vec(),
+            unlock_flag_of_rrharil: g.unlock_flag_of_rrharil.bits.to_vec(),
+            flag_of_song_record_key: g.flag_of_song_record_key.bits.to_vec(),
+            random_version_unlocked: g.random_version_unlocked.bits.to_vec(),
+            chapter8_base: wit::Chapter8Base {
+                unlock_begin: g.chapter8_base.unlock_begin,
+                unlock_second_phase: g.chapter8_base.unlock_second_phase,
+                passed: g.chapter8_base.passed,
+            },
+            chapter8_song_unlocked: g.chapter8_song_unlocked.bits.to_vec(),
+            flag_of_song_record_key_takumi: g.flag_of_song_record_key_takumi.bits.to_vec(),
+        })
+    }
+}
+
+impl TryFrom<wit::GameProgress> for SerializableGameProgress {
+    type Error = CodecError;
+    fn try_from(g: wit::GameProgress) -> Result<Self, CodecError> {
+        Ok(Self {
+            base: SerializableBase {
+                is_first_run: g.base.is_first_run,
+                legacy_chapter_finished: g.base.legacy_chapter_finished,
+                already_show_collection_tip: g.base.already_show_collection_tip,
+                already_show_auto_unlock_in_tip: g.base.already_show_auto_unlock_in_tip,
+            },
+            completed: g.completed,
+            song_update_info: g.song_update_info,
+            challenge_mode_rank: g.challenge_mode_rank.into(),
+            money: Data::from_units([
+                g.money.kib,
+                g.money.mib,
+                g.money.gib,
+                g.money.tib,
+                g.money.pib,
+            ]),
+            unlock_flag_of_spasmodic: fixed(
+                "unlock_flag_of_spasmodic",
+                g.unlock_flag_of_spasmodic,
+            )?
+            .into(),
+            unlock_flag_of_igallta: fixed("unlock_flag_of_igallta", g.unlock_flag_of_igallta)?
+                .into(),
+            unlock_flag_of_rrharil: fixed("unlock_flag_of_rrharil", g.unlock_flag_of_rrharil)?
+                .into(),
+            flag_of_song_record_key: fixed("flag_of_song_record_key", g.flag_of_song_record_key)?
+                .into(),
+            random_version_unlocked: fixed("random_version_unlocked", g.random_version_unlocked)?
+                .into(),
+            chapter8_base: SerializableChapter8Base {
+                unlock_begin: g.chapter8_base.unlock_begin,
+                unlock_second_phase: g.chapter8_base.unlock_second_phase,
+                passed: g.chapter8_base.passed,
+            },
+            chapter8_song_unlocked: fixed("chapter8_song_unlocked", g.chapter8_song_unlocked)?
+                .into(),
+            flag_of_song_record_key_takumi: fixed(
+                "flag_of_song_record_key_takumi",
+                g.flag_of_song_record_key_takumi,
+            )?
+            .into(),
+        })
+    }
+}
+
+// game_key
+impl From<SerializableGameKey> for wit::GameKey {
+    fn from(k: SerializableGameKey) -> Self {
+        Self {
+            key_list: k
+                .keys
+                .into_iter()
+                .map(|key| wit::Key {
+                    name: key.name,
+                    type_: key.ktype.to_vec(),
+                    flag: key.flag,
+                })
+                .collect(),
+            lanota_read_keys: k.lanota_read_keys.to_vec(),
+            camellia_read_key: k.camellia_read_key.to_vec(),
+            side_story4_begin_read_key: k.side_story4_begin_read_key,
+            old_score_cleared_v390: k.old_score_cleared_v390,
+        }
+    }
+}
+
+impl TryFrom<wit::GameKey> for SerializableGameKey {
+    type Error = CodecError;
+    fn try_from(k: wit::GameKey) -> Result<Self, CodecError> {
+        let keys = k
+            .key_list
+            .into_iter()
+            .map(|key| {
+                Ok(SerializableKey {
+                    ktype: fixed("type", key.type_)?,
+                    name: key.name,
+                    flag: key.flag,
+                })
+            })
+            .collect::<Result<Vec<_>, CodecError>>()?;
+        Ok(Self {
+            keys,
+            lanota_read_keys: fixed("lanota_read_keys", k.lanota_read_keys)?,
+            camellia_read_key: fixed("camellia_read_key", k.camellia_read_key)?,
+            side_story4_begin_read_key: k.side_story4_begin_read_key,
+            old_score_cleared_v390: k.old_score_cleared_v390,
+        })
+    }
+}
+
+// settings
+impl From<SerializableSettings> for wit::Settings {
+    fn from(s: SerializableSettings) -> Self {
+        Self {
+            base: wit::SettingsBase {
+                chord_support: s.base.chord_support,
+                fc_ap_indicator: s.base.fc_ap_indicator,
+                enable_hit_sound: s.base.enable_hit_sound,
+                low_resolution_mode: s.base.low_resolution_mode,
+            },
+            device_name: s.device_name,
+            bright: s.bright.get(),
+            music_volume: s.music_volume.get(),
+            effect_volume: s.effect_volume.get(),
+            hit_sound_volume: s.hit_sound_volume.get(),
+            sound_offset: s.sound_offset.get(),
+            note_scale: s.note_scale.get(),
+        }
+    }
+}
+
+impl TryFrom<wit::Settings> for SerializableSettings {
+    type Error = CodecError;
+    fn try_from(s: wit::Settings) -> Result<Self, CodecError> {
+        Ok(Self {
+            base: SerializableSettingsBase {
+                chord_support: s.base.chord_support,
+                fc_ap_indicator: s.base.fc_ap_indicator,
+                enable_hit_sound: s.base.enable_hit_sound,
+                low_resolution_mode: s.base.low_resolution_mode,
+            },
+            device_name: s.device_name,
+            bright: Brightness::new(s.bright).map_err(CodecError::Invalid)?,
+            music_volume: Volume::new(s.music_volume).map_err(CodecError::Invalid)?,
+            effect_volume: Volume::new(s.effect_volume).map_err(CodecError::Invalid)?,
+            hit_sound_volume: Volume::new(s.hit_sound_volume).map_err(CodecError::Invalid)?,
+            sound_offset: SoundOffset::new(s.sound_offset).map_err(CodecError::Invalid)?,
+            note_scale: NoteScale::new(s.note_scale).map_err(CodecError::Invalid)?,
+        })
+    }
+}
+
+macro_rules! impl_component_api {
+    ($struct_ty:ty, $serializable_ty:ty, $wit_ty:ty, $parse_fn:ident, $build_fn:ident) => {
+        fn $parse_fn(data: Vec<u8>) -> Result<$wit_ty, CodecError> {
+            let bits = BitSlice::<u8, Lsb0>::from_slice(&data);
+            let (item, _) = <$struct_ty>::parse(bits, &None).map_err(CodecError::Parse)?;
+            <$wit_ty>::try_from(<$serializable_ty>::from(item)).map_err(CodecError::from)
+        }
+
+        fn $build_fn(value: $wit_ty) -> Result<Vec<u8>, CodecError> {
+            let serializable = <$serializable_ty>::try_from(value)?;
+            let bitvec = <$struct_ty>::try_from(serializable)
+                .map_err(|e| CodecError::Build(e.to_string()))?
+                .build(&None)
+                .map_err(CodecError::Build)?;
+            Ok(bitvec.into_vec())
+        }
+    };
+}
+
+impl wit::Guest for Component {
+    impl_component_api!(User, SerializableUser, wit::User, parse_user, build_user);
+    impl_component_api!(
+        Summary,
+        SerializableSummary,
+        wit::Summary,
+        parse_summary,
+        build_summary
+    );
+    impl_component_api!(
+        GameRecord,
+        SerializableGameRecord,
+        wit::GameRecord,
+        parse_game_record,
+        build_game_record
+    );
+    impl_component_api!(
+        GameProgress,
+        SerializableGameProgress,
+        wit::GameProgress,
+        parse_game_progress,
+        build_game_progress
+    );
+    impl_component_api!(
+        GameKey,
+        SerializableGameKey,
+        wit::GameKey,
+        parse_game_key,
+        build_game_key
+    );
+    impl_component_api!(
+        Settings,
+        SerializableSettings,
+        wit::Settings,
+        parse_settings,
+        build_settings
+    );
+}
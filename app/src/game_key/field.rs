@@ -11,12 +11,13 @@ pub struct Key {
     pub length: u8,
     #[binary_field(align = 8)]
     pub ktype: [bool; 5],
+    /// 每个已设置的类型对应一个字节
     #[binary_field(size_func = get_flag_len,align = 8,sub_align = 1)]
-    pub flag: Vec<bool>,
+    pub flag: Vec<u8>,
 }
 impl Key {
     fn get_flag_len(&self) -> usize {
-        return (self.length).saturating_sub(1) as usize;
+        (self.length).saturating_sub(1) as usize
     }
 }
 
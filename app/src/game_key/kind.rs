@@ -0,0 +1,158 @@
+use super::serde::SerializableKey;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+/// `Key.type` 各位的含义
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum KeyKind {
+    ReadCollection,
+    SingleUnlock,
+    /// flag 为已收集的数量
+    Collection,
+    Illustration,
+    Avatar,
+}
+
+impl KeyKind {
+    pub const ALL: [KeyKind; 5] = [
+        KeyKind::ReadCollection,
+        KeyKind::SingleUnlock,
+        KeyKind::Collection,
+        KeyKind::Illustration,
+        KeyKind::Avatar,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl SerializableKey {
+    /// `progress` 中的每个类型按顺序对应一个 flag 字节
+    pub fn new(name: impl Into<String>, progress: &BTreeMap<KeyKind, u8>) -> Self {
+        let mut ktype = [false; 5];
+        for kind in progress.keys() {
+            ktype[kind.index()] = true;
+        }
+        SerializableKey {
+            name: name.into(),
+            ktype,
+            flag: progress.values().copied().collect(),
+        }
+    }
+
+    pub fn has(&self, kind: KeyKind) -> bool {
+        self.ktype[kind.index()]
+    }
+
+    pub fn kinds(&self) -> impl Iterator<Item = KeyKind> + '_ {
+        KeyKind::ALL.into_iter().filter(|k| self.has(*k))
+    }
+
+    /// 该类型对应的 flag 值, 类型未设置或 flag 不足时为 None
+    pub fn progress(&self, kind: KeyKind) -> Option<u8> {
+        let position = self.kinds().position(|k| k == kind)?;
+        self.flag.get(position).copied()
+    }
+
+    /// flag 数量与已设置的类型数量一致
+    pub fn is_consistent(&self) -> bool {
+        self.kinds().count() == self.flag.len()
+    }
+
+    pub fn progress_map(&self) -> BTreeMap<KeyKind, u8> {
+        self.kinds()
+            .filter_map(|k| Some((k, self.progress(k)?)))
+            .collect()
+    }
+
+    /// flag 与类型对不上时为 None
+    pub fn structured(&self) -> Option<StructuredKey> {
+        self.is_consistent().then(|| StructuredKey {
+            name: self.name.clone(),
+            progress: self.progress_map(),
+        })
+    }
+}
+
+/// 形如 `{ name, progress: { "collection": 2 } }`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct StructuredKey {
+    pub name: String,
+    pub progress: BTreeMap<KeyKind, u8>,
+}
+
+// 总是序列化为 `{ name, type, flag }`, 反序列化也接受 [`StructuredKey`] 的形式,
+// 需要结构化输出时使用 [`SerializableKey::structured`].
+// 旧版本的 flag 为 bool 数组, 仍然接受
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub(super) enum KeyRepr {
+    Raw {
+        name: String,
+        #[serde(rename = "type")]
+        ktype: [bool; 5],
+        #[serde(deserialize_with = "flag_values")]
+        flag: Vec<u8>,
+    },
+    Structured(StructuredKey),
+}
+
+fn flag_values<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum FlagValue {
+        Count(u8),
+        Bool(bool),
+    }
+    let values = Vec::<FlagValue>::deserialize(deserializer)?;
+    Ok(values
+        .into_iter()
+        .map(|v| match v {
+            FlagValue::Count(n) => n,
+            FlagValue::Bool(b) => b as u8,
+        })
+        .collect())
+}
+
+impl TryFrom<KeyRepr> for SerializableKey {
+    type Error = String;
+
+    fn try_from(repr: KeyRepr) -> Result<Self, String> {
+        match repr {
+            KeyRepr::Raw { name, ktype, flag } => {
+                let key = SerializableKey { name, ktype, flag };
+                key.validate()?;
+                Ok(key)
+            }
+            KeyRepr::Structured(StructuredKey { name, progress }) => {
+                Ok(SerializableKey::new(name, &progress))
+            }
+        }
+    }
+}
+
+impl From<SerializableKey> for KeyRepr {
+    fn from(key: SerializableKey) -> Self {
+        KeyRepr::Raw {
+            name: key.name,
+            ktype: key.ktype,
+            flag: key.flag,
+        }
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for SerializableKey {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "SerializableKey".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        KeyRepr::json_schema(generator)
+    }
+}
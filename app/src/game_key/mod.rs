@@ -1,2 +1,3 @@
 pub mod field;
+pub mod kind;
 pub mod serde;
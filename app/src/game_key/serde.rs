@@ -1,8 +1,10 @@
 use super::field::*;
+use super::kind::KeyRepr;
 use crate::phi_base::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SerializableGameKey {
     #[serde(rename = "key_list")]
     pub keys: Vec<SerializableKey>,
@@ -12,12 +14,13 @@ pub struct SerializableGameKey {
     pub old_score_cleared_v390: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+/// 序列化格式见 [`super::kind`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "KeyRepr", into = "KeyRepr")]
 pub struct SerializableKey {
     pub name: String,
-    #[serde(rename = "type")]
     pub ktype: [bool; 5],
-    pub flag: Vec<bool>,
+    pub flag: Vec<u8>,
 }
 
 impl From<GameKey> for SerializableGameKey {
@@ -41,32 +44,50 @@ impl From<GameKey> for SerializableGameKey {
     }
 }
 
-impl From<SerializableGameKey> for GameKey {
-    fn from(sgk: SerializableGameKey) -> Self {
-        let key_sum = sgk.keys.len();
+impl SerializableKey {
+    /// `length` 为 flag 数 + 1, 只有一个字节
+    pub const MAX_FLAGS: usize = u8::MAX as usize - 1;
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.flag.len() > Self::MAX_FLAGS {
+            return Err(format!("Key error: {}: too many flags", self.name));
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<SerializableGameKey> for GameKey {
+    type Error = String;
+
+    fn try_from(sgk: SerializableGameKey) -> Result<Self, String> {
+        // 两字节 VarInt 最大为 0x7fff
+        let key_sum = u16::try_from(sgk.keys.len())
+            .ok()
+            .filter(|n| *n <= 0x7fff)
+            .ok_or_else(|| format!("Key error: too many keys: {}", sgk.keys.len()))?;
         let key_list = sgk
             .keys
             .into_iter()
             .map(|sk| {
-                let length = sk.flag.len() as u8 + 1; // flag 长度 + 1 = length
-                Key {
+                sk.validate()?;
+                Ok(Key {
+                    length: sk.flag.len() as u8 + 1,
                     name: sk.name.into(),
-                    length,
                     ktype: sk.ktype,
                     flag: sk.flag,
-                }
+                })
             })
-            .collect();
+            .collect::<Result<_, String>>()?;
 
-        GameKey {
+        Ok(GameKey {
             key_list: KeyList {
-                key_sum: VarInt(key_sum as u16),
+                key_sum: VarInt(key_sum),
                 key_list,
             },
             lanota_read_keys: sgk.lanota_read_keys,
             camellia_read_key: sgk.camellia_read_key,
             side_story4_begin_read_key: sgk.side_story4_begin_read_key,
             old_score_cleared_v390: sgk.old_score_cleared_v390,
-        }
+        })
     }
 }
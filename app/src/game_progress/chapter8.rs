@@ -0,0 +1,133 @@
+use super::flags::{Chapter8SongFlag, FlagSet, UnlockFlag};
+use super::serde::{SerializableChapter8Base, SerializableGameProgress};
+use serde::Serialize;
+
+pub const CHAPTER8_SONGS: u8 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Chapter8Stage {
+    Locked,
+    FirstPhase,
+    SecondPhase,
+    Passed,
+}
+
+/// 第八章进度, 由 `chapter8_base` 与 `chapter8_song_unlocked` 推出
+///
+/// 阶段依次解锁; 歌曲的解锁顺序没有限制, 只要求未开始时没有歌曲解锁,
+/// 通关时全部歌曲均已解锁. 字段只能通过 [`Chapter8Progress::new`] 等方法构造, 总是一致的
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Chapter8Progress {
+    stage: Chapter8Stage,
+    /// 已解锁的歌曲
+    songs: FlagSet<Chapter8SongFlag, 6>,
+}
+
+impl Chapter8Progress {
+    pub const LOCKED: Chapter8Progress = Chapter8Progress {
+        stage: Chapter8Stage::Locked,
+        songs: FlagSet::new([false; 6]),
+    };
+
+    pub fn new(stage: Chapter8Stage, songs: FlagSet<Chapter8SongFlag, 6>) -> Result<Self, String> {
+        let progress = Chapter8Progress { stage, songs };
+        if stage == Chapter8Stage::Locked && !songs.is_empty() {
+            return Err("Chapter8 error: songs unlocked before chapter begins".to_string());
+        }
+        if stage == Chapter8Stage::Passed && progress.song_count() < CHAPTER8_SONGS {
+            return Err("Chapter8 error: passed with locked songs".to_string());
+        }
+        Ok(progress)
+    }
+
+    pub fn from_flags(
+        base: &SerializableChapter8Base,
+        songs: &FlagSet<Chapter8SongFlag, 6>,
+    ) -> Result<Self, String> {
+        let stage = match (base.unlock_begin, base.unlock_second_phase, base.passed) {
+            (false, false, false) => Chapter8Stage::Locked,
+            (true, false, false) => Chapter8Stage::FirstPhase,
+            (true, true, false) => Chapter8Stage::SecondPhase,
+            (true, true, true) => Chapter8Stage::Passed,
+            _ => return Err("Chapter8 error: stage flags out of order".to_string()),
+        };
+        Chapter8Progress::new(stage, *songs)
+    }
+
+    pub fn stage(self) -> Chapter8Stage {
+        self.stage
+    }
+
+    /// 已解锁的歌曲数
+    pub fn song_count(self) -> u8 {
+        self.songs.iter().count() as u8
+    }
+
+    pub fn base(self) -> SerializableChapter8Base {
+        SerializableChapter8Base {
+            unlock_begin: self.stage >= Chapter8Stage::FirstPhase,
+            unlock_second_phase: self.stage >= Chapter8Stage::SecondPhase,
+            passed: self.stage == Chapter8Stage::Passed,
+        }
+    }
+
+    pub fn song_flags(self) -> FlagSet<Chapter8SongFlag, 6> {
+        self.songs
+    }
+
+    fn transition(self, from: Chapter8Stage, to: Chapter8Stage) -> Result<Self, String> {
+        if self.stage != from {
+            return Err(format!(
+                "Chapter8 error: cannot go from {:?} to {:?}",
+                self.stage, to
+            ));
+        }
+        Chapter8Progress::new(to, self.songs)
+    }
+
+    pub fn begin(self) -> Result<Self, String> {
+        self.transition(Chapter8Stage::Locked, Chapter8Stage::FirstPhase)
+    }
+
+    pub fn enter_second_phase(self) -> Result<Self, String> {
+        self.transition(Chapter8Stage::FirstPhase, Chapter8Stage::SecondPhase)
+    }
+
+    pub fn unlock_song(self, song: Chapter8SongFlag) -> Result<Self, String> {
+        if !matches!(
+            self.stage,
+            Chapter8Stage::FirstPhase | Chapter8Stage::SecondPhase
+        ) || self.songs.contains(song)
+        {
+            return Err(format!(
+                "Chapter8 error: cannot unlock {} in {:?}",
+                song.name(),
+                self.stage
+            ));
+        }
+        let mut songs = self.songs;
+        songs.set(song, true);
+        Chapter8Progress::new(self.stage, songs)
+    }
+
+    /// 需要先解锁全部歌曲
+    pub fn pass(self) -> Result<Self, String> {
+        if self.song_count() < CHAPTER8_SONGS {
+            return Err("Chapter8 error: cannot pass with locked songs".to_string());
+        }
+        self.transition(Chapter8Stage::SecondPhase, Chapter8Stage::Passed)
+    }
+}
+
+impl SerializableGameProgress {
+    pub fn chapter8(&self) -> Result<Chapter8Progress, String> {
+        Chapter8Progress::from_flags(&self.chapter8_base, &self.chapter8_song_unlocked)
+    }
+
+    /// 同时写入阶段标记与歌曲标记
+    pub fn set_chapter8(&mut self, progress: Chapter8Progress) {
+        self.chapter8_base = progress.base();
+        self.chapter8_song_unlocked = progress.song_flags();
+    }
+}
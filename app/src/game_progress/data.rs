@@ -0,0 +1,129 @@
+use super::serde::SerializableMoney;
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+
+const UNIT_NAMES: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+const CARRY: u64 = 1024;
+
+/// 数据 (游戏内货币) 总量, 以 KiB 为最小单位
+///
+/// 以 [`SerializableMoney`] 的形式序列化, 解析时按 1024 进位求和, 输出与构建时规范化
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(from = "SerializableMoney")]
+pub struct Data(u64);
+
+impl Data {
+    pub const ZERO: Data = Data(0);
+
+    pub fn from_kib(kib: u64) -> Self {
+        Data(kib)
+    }
+
+    pub fn kib(self) -> u64 {
+        self.0
+    }
+
+    /// 不足 1 KiB 的部分舍去
+    pub fn from_bytes(bytes: u128) -> Result<Self, String> {
+        u64::try_from(bytes / CARRY as u128)
+            .map(Data)
+            .map_err(|_| format!("Data error: {} bytes out of range", bytes))
+    }
+
+    pub fn bytes(self) -> u128 {
+        self.0 as u128 * CARRY as u128
+    }
+
+    /// 各单位的数值可超过 1023, 按 1024 进位求和
+    pub fn from_units(units: [u16; 5]) -> Self {
+        Data(
+            units
+                .iter()
+                .rev()
+                .fold(0, |total, &unit| total * CARRY + unit as u64),
+        )
+    }
+
+    /// 规范化后的 KiB..PiB, 除 PiB 外均小于 1024
+    pub fn units(self) -> Result<[u16; 5], String> {
+        let mut units = [0u16; 5];
+        let mut rest = self.0;
+        for unit in units.iter_mut().take(4) {
+            *unit = (rest % CARRY) as u16;
+            rest /= CARRY;
+        }
+        units[4] = u16::try_from(rest).map_err(|_| format!("Data error: {} PiB overflow", rest))?;
+        Ok(units)
+    }
+
+    pub fn checked_add(self, other: Data) -> Option<Data> {
+        self.0.checked_add(other.0).map(Data)
+    }
+
+    /// 不够扣除时返回 None
+    pub fn checked_sub(self, other: Data) -> Option<Data> {
+        self.0.checked_sub(other.0).map(Data)
+    }
+}
+
+/// 取不小于 1 的最大单位, KiB 显示整数, 其余保留一位小数, 如 `12.3 MiB`
+impl fmt::Display for Data {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut unit = 0;
+        let mut scale = 1u64;
+        while unit + 1 < UNIT_NAMES.len() && self.0 >= scale * CARRY {
+            unit += 1;
+            scale *= CARRY;
+        }
+        if unit == 0 {
+            write!(f, "{} {}", self.0, UNIT_NAMES[0])
+        } else {
+            write!(
+                f,
+                "{:.1} {}",
+                self.0 as f64 / scale as f64,
+                UNIT_NAMES[unit]
+            )
+        }
+    }
+}
+
+impl From<SerializableMoney> for Data {
+    fn from(m: SerializableMoney) -> Self {
+        Data::from_units([m.kib, m.mib, m.gib, m.tib, m.pib])
+    }
+}
+
+impl TryFrom<Data> for SerializableMoney {
+    type Error = String;
+
+    fn try_from(data: Data) -> Result<Self, String> {
+        let [kib, mib, gib, tib, pib] = data.units()?;
+        Ok(SerializableMoney {
+            kib,
+            mib,
+            gib,
+            tib,
+            pib,
+        })
+    }
+}
+
+impl Serialize for Data {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializableMoney::try_from(*self)
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Data {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Money".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        SerializableMoney::json_schema(generator)
+    }
+}
@@ -0,0 +1,358 @@
+use super::serde::SerializableGameProgress;
+use crate::game_record::song_id::SongId;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+const BUILTIN: &str = include_str!("../../data/unlocks.tsv");
+
+/// 解锁标记数组中的一位
+pub trait UnlockFlag:
+    Copy + Eq + fmt::Debug + Serialize + for<'de> Deserialize<'de> + 'static
+{
+    /// 对应的 gameProgress 字段名
+    const FIELD: &'static str;
+    const ALL: &'static [Self];
+
+    fn name(self) -> &'static str;
+
+    fn index(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|f| *f == self)
+            .unwrap_or_default()
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|f| f.name() == name)
+    }
+}
+
+macro_rules! unlock_flag {
+    ($(#[$meta:meta])* $name:ident, $field:literal, [$($member:ident = $key:literal),+ $(,)?]) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+        #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+        pub enum $name {
+            $(#[serde(rename = $key)] $member),+
+        }
+
+        impl UnlockFlag for $name {
+            const FIELD: &'static str = $field;
+            const ALL: &'static [Self] = &[$($name::$member),+];
+
+            fn name(self) -> &'static str {
+                match self {
+                    $($name::$member => $key),+
+                }
+            }
+        }
+    };
+}
+
+// 各位对应的歌曲因版本而异, 这里只按顺序命名, 歌曲由 [`UnlockTable`] 给出
+unlock_flag!(
+    /// Spasmodic 的解锁步骤
+    SpasmodicFlag,
+    "unlock_flag_of_spasmodic",
+    [Step1 = "step1", Step2 = "step2", Step3 = "step3", Step4 = "step4"]
+);
+unlock_flag!(
+    /// Igallta 的解锁步骤
+    IgalltaFlag,
+    "unlock_flag_of_igallta",
+    [Step1 = "step1", Step2 = "step2", Step3 = "step3", Step4 = "step4"]
+);
+unlock_flag!(
+    /// Rrhar'il 的解锁步骤
+    RrharilFlag,
+    "unlock_flag_of_rrharil",
+    [Step1 = "step1", Step2 = "step2", Step3 = "step3", Step4 = "step4"]
+);
+unlock_flag!(
+    SongRecordKeyFlag,
+    "flag_of_song_record_key",
+    [
+        Key1 = "key1",
+        Key2 = "key2",
+        Key3 = "key3",
+        Key4 = "key4",
+        Key5 = "key5",
+        Key6 = "key6",
+        Key7 = "key7",
+        Key8 = "key8",
+    ]
+);
+unlock_flag!(
+    /// Random 的各个版本
+    RandomVersionFlag,
+    "random_version_unlocked",
+    [
+        Version1 = "version1",
+        Version2 = "version2",
+        Version3 = "version3",
+        Version4 = "version4",
+        Version5 = "version5",
+        Version6 = "version6",
+    ]
+);
+unlock_flag!(
+    /// 第八章的歌曲
+    Chapter8SongFlag,
+    "chapter8_song_unlocked",
+    [
+        Song1 = "song1",
+        Song2 = "song2",
+        Song3 = "song3",
+        Song4 = "song4",
+        Song5 = "song5",
+        Song6 = "song6",
+    ]
+);
+unlock_flag!(
+    TakumiKeyFlag,
+    "flag_of_song_record_key_takumi",
+    [Key1 = "key1", Key2 = "key2", Key3 = "key3"]
+);
+
+/// 定长的 bool 数组, 每一位对应 `F` 的一个成员
+///
+/// 序列化为已设置成员的名称列表, 反序列化也接受存档中的 bool 数组,
+/// 二进制格式仍按位读写 [`FlagSet::bits`]
+pub struct FlagSet<F, const N: usize> {
+    pub bits: [bool; N],
+    flag: PhantomData<F>,
+}
+
+impl<F, const N: usize> FlagSet<F, N> {
+    pub const fn new(bits: [bool; N]) -> Self {
+        FlagSet {
+            bits,
+            flag: PhantomData,
+        }
+    }
+}
+
+impl<F: UnlockFlag, const N: usize> FlagSet<F, N> {
+    pub fn contains(&self, flag: F) -> bool {
+        self.bits.get(flag.index()).copied().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, flag: F, value: bool) {
+        if let Some(bit) = self.bits.get_mut(flag.index()) {
+            *bit = value;
+        }
+    }
+
+    /// 已设置的成员
+    pub fn iter(&self) -> impl Iterator<Item = F> + '_ {
+        F::ALL.iter().copied().filter(|f| self.contains(*f))
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.iter().map(F::name).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.bits.contains(&true)
+    }
+}
+
+impl<F, const N: usize> Clone for FlagSet<F, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F, const N: usize> Copy for FlagSet<F, N> {}
+
+impl<F, const N: usize> PartialEq for FlagSet<F, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits
+    }
+}
+
+impl<F, const N: usize> Eq for FlagSet<F, N> {}
+
+impl<F, const N: usize> Default for FlagSet<F, N> {
+    fn default() -> Self {
+        FlagSet {
+            bits: [false; N],
+            flag: PhantomData,
+        }
+    }
+}
+
+impl<F: UnlockFlag, const N: usize> fmt::Debug for FlagSet<F, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<F: UnlockFlag, const N: usize> From<[bool; N]> for FlagSet<F, N> {
+    fn from(bits: [bool; N]) -> Self {
+        FlagSet::new(bits)
+    }
+}
+
+impl<F, const N: usize> From<FlagSet<F, N>> for [bool; N] {
+    fn from(set: FlagSet<F, N>) -> Self {
+        set.bits
+    }
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+enum FlagRepr<F> {
+    Raw(Vec<bool>),
+    Named(Vec<F>),
+}
+
+impl<F: UnlockFlag, const N: usize> Serialize for FlagSet<F, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.iter().collect::<Vec<F>>().serialize(serializer)
+    }
+}
+
+impl<'de, F: UnlockFlag, const N: usize> Deserialize<'de> for FlagSet<F, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut set = FlagSet::default();
+        match FlagRepr::<F>::deserialize(deserializer)? {
+            // 空列表是没有设置任何成员的名称列表
+            FlagRepr::Raw(bits) if bits.is_empty() => {}
+            FlagRepr::Raw(bits) => {
+                set.bits = bits.try_into().map_err(|bits: Vec<bool>| {
+                    serde::de::Error::invalid_length(bits.len(), &N.to_string().as_str())
+                })?;
+            }
+            FlagRepr::Named(flags) => {
+                for flag in flags {
+                    set.set(flag, true);
+                }
+            }
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(feature = "schema")]
+impl<F: UnlockFlag + schemars::JsonSchema, const N: usize> schemars::JsonSchema for FlagSet<F, N> {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        format!("FlagSet_{}", F::schema_name()).into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        FlagRepr::<F>::json_schema(generator)
+    }
+}
+
+/// 标记 -> 歌曲, 每行 `字段名\t成员名\t歌曲ID`, 如 `chapter8_song_unlocked\tsong1\t<ID>`,
+/// 内置表来自 `data/unlocks.tsv`
+#[derive(Debug, Clone, Default)]
+pub struct UnlockTable(BTreeMap<(String, String), Vec<SongId>>);
+
+impl UnlockTable {
+    pub fn builtin() -> Result<Self, String> {
+        UnlockTable::from_tsv(BUILTIN)
+    }
+
+    pub fn from_tsv(text: &str) -> Result<Self, String> {
+        let mut table = UnlockTable::default();
+        for (line_no, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let cols: Vec<&str> = line.split('\t').map(str::trim).collect();
+            let [field, member, song] = cols[..] else {
+                return Err(format!(
+                    "Unlock table error: line {}: expected 3 columns",
+                    line_no + 1
+                ));
+            };
+            if !SerializableGameProgress::flag_members(field).contains(&member) {
+                return Err(format!(
+                    "Unlock table error: line {}: unknown flag {}.{}",
+                    line_no + 1,
+                    field,
+                    member
+                ));
+            }
+            let song = song
+                .parse()
+                .map_err(|e| format!("Unlock table error: line {}: {}", line_no + 1, e))?;
+            table.insert(field, member, song);
+        }
+        Ok(table)
+    }
+
+    pub fn insert(&mut self, field: &str, member: &str, song: SongId) {
+        self.0
+            .entry((field.to_string(), member.to_string()))
+            .or_default()
+            .push(song);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn songs(&self, field: &str, member: &str) -> &[SongId] {
+        self.0
+            .get(&(field.to_string(), member.to_string()))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+impl SerializableGameProgress {
+    /// 各标记字段中已设置的成员, 按字段顺序
+    pub fn unlock_flags(&self) -> Vec<(&'static str, Vec<&'static str>)> {
+        vec![
+            (SpasmodicFlag::FIELD, self.unlock_flag_of_spasmodic.names()),
+            (IgalltaFlag::FIELD, self.unlock_flag_of_igallta.names()),
+            (RrharilFlag::FIELD, self.unlock_flag_of_rrharil.names()),
+            (
+                SongRecordKeyFlag::FIELD,
+                self.flag_of_song_record_key.names(),
+            ),
+            (
+                RandomVersionFlag::FIELD,
+                self.random_version_unlocked.names(),
+            ),
+            (Chapter8SongFlag::FIELD, self.chapter8_song_unlocked.names()),
+            (
+                TakumiKeyFlag::FIELD,
+                self.flag_of_song_record_key_takumi.names(),
+            ),
+        ]
+    }
+
+    fn flag_members(field: &str) -> Vec<&'static str> {
+        fn names<F: UnlockFlag>() -> Vec<&'static str> {
+            F::ALL.iter().map(|f| f.name()).collect()
+        }
+        match field {
+            SpasmodicFlag::FIELD => names::<SpasmodicFlag>(),
+            IgalltaFlag::FIELD => names::<IgalltaFlag>(),
+            RrharilFlag::FIELD => names::<RrharilFlag>(),
+            SongRecordKeyFlag::FIELD => names::<SongRecordKeyFlag>(),
+            RandomVersionFlag::FIELD => names::<RandomVersionFlag>(),
+            Chapter8SongFlag::FIELD => names::<Chapter8SongFlag>(),
+            TakumiKeyFlag::FIELD => names::<TakumiKeyFlag>(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// 通过这些标记解锁的歌曲
+    pub fn unlocked_songs<'a>(&self, table: &'a UnlockTable) -> Vec<&'a SongId> {
+        self.unlock_flags()
+            .into_iter()
+            .flat_map(|(field, members)| {
+                members
+                    .into_iter()
+                    .flat_map(move |member| table.songs(field, member))
+            })
+            .collect()
+    }
+}
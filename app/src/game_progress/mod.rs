@@ -1,2 +1,5 @@
+pub mod chapter8;
+pub mod data;
 pub mod field;
+pub mod flags;
 pub mod serde;
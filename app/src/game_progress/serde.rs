@@ -1,8 +1,12 @@
+use super::data::Data;
 use super::field::{Chapter8Base, GameProgress, Money, ProgressBase};
+use super::flags::*;
+use crate::challenge_rank::ChallengeRank;
 use crate::phi_base::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SerializableBase {
     pub is_first_run: bool,
     pub legacy_chapter_finished: bool,
@@ -10,7 +14,9 @@ pub struct SerializableBase {
     pub already_show_auto_unlock_in_tip: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+/// [`Data`] 在 JSON 中的形式, 序列化时已规范化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SerializableMoney {
     pub kib: u16,
     pub mib: u16,
@@ -19,28 +25,30 @@ pub struct SerializableMoney {
     pub pib: u16,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SerializableChapter8Base {
     pub unlock_begin: bool,
     pub unlock_second_phase: bool,
     pub passed: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SerializableGameProgress {
     pub base: SerializableBase,
     pub completed: String,
     pub song_update_info: u16,
-    pub challenge_mode_rank: u16,
-    pub money: SerializableMoney,
-    pub unlock_flag_of_spasmodic: [bool; 4],
-    pub unlock_flag_of_igallta: [bool; 4],
-    pub unlock_flag_of_rrharil: [bool; 4],
-    pub flag_of_song_record_key: [bool; 8],
-    pub random_version_unlocked: [bool; 6],
+    pub challenge_mode_rank: ChallengeRank,
+    pub money: Data,
+    pub unlock_flag_of_spasmodic: FlagSet<SpasmodicFlag, 4>,
+    pub unlock_flag_of_igallta: FlagSet<IgalltaFlag, 4>,
+    pub unlock_flag_of_rrharil: FlagSet<RrharilFlag, 4>,
+    pub flag_of_song_record_key: FlagSet<SongRecordKeyFlag, 8>,
+    pub random_version_unlocked: FlagSet<RandomVersionFlag, 6>,
     pub chapter8_base: SerializableChapter8Base,
-    pub chapter8_song_unlocked: [bool; 6],
-    pub flag_of_song_record_key_takumi: [bool; 3],
+    pub chapter8_song_unlocked: FlagSet<Chapter8SongFlag, 6>,
+    pub flag_of_song_record_key_takumi: FlagSet<TakumiKeyFlag, 3>,
 }
 
 impl From<ProgressBase> for SerializableBase {
@@ -65,27 +73,24 @@ impl From<SerializableBase> for ProgressBase {
     }
 }
 
-impl From<Money> for SerializableMoney {
+impl From<Money> for Data {
     fn from(m: Money) -> Self {
-        Self {
-            kib: m.kib.0,
-            mib: m.mib.0,
-            gib: m.gib.0,
-            tib: m.tib.0,
-            pib: m.pib.0,
-        }
+        Data::from_units([m.kib.0, m.mib.0, m.gib.0, m.tib.0, m.pib.0])
     }
 }
 
-impl From<SerializableMoney> for Money {
-    fn from(m: SerializableMoney) -> Self {
-        Money {
-            kib: VarInt(m.kib),
-            mib: VarInt(m.mib),
-            gib: VarInt(m.gib),
-            tib: VarInt(m.tib),
-            pib: VarInt(m.pib),
-        }
+impl TryFrom<Data> for Money {
+    type Error = String;
+
+    fn try_from(data: Data) -> Result<Self, String> {
+        let [kib, mib, gib, tib, pib] = data.units()?.map(VarInt);
+        Ok(Money {
+            kib,
+            mib,
+            gib,
+            tib,
+            pib,
+        })
     }
 }
 
@@ -115,36 +120,39 @@ impl From<GameProgress> for SerializableGameProgress {
             base: g.base.into(),
             completed: g.completed.0,
             song_update_info: g.song_update_info.0,
-            challenge_mode_rank: g.challenge_mode_rank,
+            challenge_mode_rank: g.challenge_mode_rank.into(),
             money: g.money.into(),
-            unlock_flag_of_spasmodic: g.unlock_flag_of_spasmodic,
-            unlock_flag_of_igallta: g.unlock_flag_of_igallta,
-            unlock_flag_of_rrharil: g.unlock_flag_of_rrharil,
-            flag_of_song_record_key: g.flag_of_song_record_key,
-            random_version_unlocked: g.random_version_unlocked,
+            unlock_flag_of_spasmodic: g.unlock_flag_of_spasmodic.into(),
+            unlock_flag_of_igallta: g.unlock_flag_of_igallta.into(),
+            unlock_flag_of_rrharil: g.unlock_flag_of_rrharil.into(),
+            flag_of_song_record_key: g.flag_of_song_record_key.into(),
+            random_version_unlocked: g.random_version_unlocked.into(),
             chapter8_base: g.chapter8_base.into(),
-            chapter8_song_unlocked: g.chapter8_song_unlocked,
-            flag_of_song_record_key_takumi: g.flag_of_song_record_key_takumi,
+            chapter8_song_unlocked: g.chapter8_song_unlocked.into(),
+            flag_of_song_record_key_takumi: g.flag_of_song_record_key_takumi.into(),
         }
     }
 }
 
-impl From<SerializableGameProgress> for GameProgress {
-    fn from(g: SerializableGameProgress) -> Self {
-        GameProgress {
+impl TryFrom<SerializableGameProgress> for GameProgress {
+    type Error = String;
+
+    fn try_from(g: SerializableGameProgress) -> Result<Self, String> {
+        g.challenge_mode_rank.validate()?;
+        Ok(GameProgress {
             base: g.base.into(),
             completed: PhiString(g.completed),
             song_update_info: VarInt(g.song_update_info),
-            challenge_mode_rank: g.challenge_mode_rank,
-            money: g.money.into(),
-            unlock_flag_of_spasmodic: g.unlock_flag_of_spasmodic,
-            unlock_flag_of_igallta: g.unlock_flag_of_igallta,
-            unlock_flag_of_rrharil: g.unlock_flag_of_rrharil,
-            flag_of_song_record_key: g.flag_of_song_record_key,
-            random_version_unlocked: g.random_version_unlocked,
+            challenge_mode_rank: g.challenge_mode_rank.into(),
+            money: g.money.try_into()?,
+            unlock_flag_of_spasmodic: g.unlock_flag_of_spasmodic.into(),
+            unlock_flag_of_igallta: g.unlock_flag_of_igallta.into(),
+            unlock_flag_of_rrharil: g.unlock_flag_of_rrharil.into(),
+            flag_of_song_record_key: g.flag_of_song_record_key.into(),
+            random_version_unlocked: g.random_version_unlocked.into(),
             chapter8_base: g.chapter8_base.into(),
-            chapter8_song_unlocked: g.chapter8_song_unlocked,
-            flag_of_song_record_key_takumi: g.flag_of_song_record_key_takumi,
-        }
+            chapter8_song_unlocked: g.chapter8_song_unlocked.into(),
+            flag_of_song_record_key_takumi: g.flag_of_song_record_key_takumi.into(),
+        })
     }
 }
@@ -0,0 +1,98 @@
+use super::difficulty::Difficulty;
+use super::grade::MAX_SCORE;
+use super::serde::{SerializableGameRecord, SerializableLevelRecord};
+use super::song_id::SongId;
+use serde::Serialize;
+
+/// 分数中 acc 部分的满分, 其余 100000 由最大连击给出
+const ACC_SCORE: f32 = 900_000.0;
+const COMBO_SCORE: f32 = 100_000.0;
+/// acc 以 f32 存储, 允许的分数误差
+const SCORE_TOLERANCE: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyFlag {
+    AccNan,
+    AccOutOfRange,
+    ScoreOverMax,
+    ApWithoutFc,
+    FcOnZeroScore,
+    /// score 与 acc 无法同时出现
+    ScoreAccMismatch,
+    /// 歌曲键无法解析为 SongId, rks 等计算会忽略该谱面
+    InvalidSongId,
+}
+
+impl AnomalyFlag {
+    pub fn check(score: u32, acc: f32, fc: bool) -> Vec<AnomalyFlag> {
+        let mut flags = Vec::new();
+        if acc.is_nan() {
+            flags.push(AnomalyFlag::AccNan);
+        } else if !(0.0..=100.0).contains(&acc) {
+            flags.push(AnomalyFlag::AccOutOfRange);
+        }
+        if score > MAX_SCORE {
+            flags.push(AnomalyFlag::ScoreOverMax);
+        }
+        if score == MAX_SCORE && !fc {
+            flags.push(AnomalyFlag::ApWithoutFc);
+        }
+        if score == 0 && fc {
+            flags.push(AnomalyFlag::FcOnZeroScore);
+        }
+        if flags.is_empty() && !score_matches_acc(score, acc, fc) {
+            flags.push(AnomalyFlag::ScoreAccMismatch);
+        }
+        flags
+    }
+}
+
+/// score = 900000 * acc + 100000 * 最大连击 / 物量, FC 时连击部分为满
+fn score_matches_acc(score: u32, acc: f32, fc: bool) -> bool {
+    if score == MAX_SCORE || acc >= 100.0 {
+        return score == MAX_SCORE && acc >= 100.0;
+    }
+    let combo = score as f32 - ACC_SCORE * acc / 100.0;
+    if fc {
+        (combo - COMBO_SCORE).abs() <= SCORE_TOLERANCE
+    } else {
+        (-SCORE_TOLERANCE..=COMBO_SCORE + SCORE_TOLERANCE).contains(&combo)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChartAnomaly {
+    pub song: String,
+    pub difficulty: Difficulty,
+    pub flags: Vec<AnomalyFlag>,
+}
+
+impl SerializableLevelRecord {
+    pub fn anomalies(&self) -> Vec<AnomalyFlag> {
+        AnomalyFlag::check(self.score, self.acc, self.fc)
+    }
+}
+
+impl SerializableGameRecord {
+    /// 只返回存在异常的谱面
+    pub fn anomalies(&self) -> Vec<ChartAnomaly> {
+        self.0
+            .iter()
+            .flat_map(|(song, levels)| {
+                let invalid = song.parse::<SongId>().is_err();
+                levels.iter().filter_map(move |(diff, record)| {
+                    let mut flags = record.anomalies();
+                    if invalid {
+                        flags.push(AnomalyFlag::InvalidSongId);
+                    }
+                    (!flags.is_empty()).then(|| ChartAnomaly {
+                        song: song.clone(),
+                        difficulty: *diff,
+                        flags,
+                    })
+                })
+            })
+            .collect()
+    }
+}
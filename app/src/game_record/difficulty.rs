@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 难度, 顺序与存档中 `unlock`/`fc` 数组的下标一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Difficulty {
+    #[serde(rename = "EZ")]
+    Ez,
+    #[serde(rename = "HD")]
+    Hd,
+    #[serde(rename = "IN")]
+    In,
+    #[serde(rename = "AT")]
+    At,
+    Legacy,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 5] = [
+        Difficulty::Ez,
+        Difficulty::Hd,
+        Difficulty::In,
+        Difficulty::At,
+        Difficulty::Legacy,
+    ];
+
+    /// 有定数的难度, Legacy 不计入 rks 与 summary
+    pub const RATED: [Difficulty; 4] = [
+        Difficulty::Ez,
+        Difficulty::Hd,
+        Difficulty::In,
+        Difficulty::At,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Ez => "EZ",
+            Difficulty::Hd => "HD",
+            Difficulty::In => "IN",
+            Difficulty::At => "AT",
+            Difficulty::Legacy => "Legacy",
+        }
+    }
+
+    pub fn is_rated(self) -> bool {
+        self != Difficulty::Legacy
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|d| d.name() == s)
+            .ok_or_else(|| format!("unknown difficulty: {:?}", s))
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
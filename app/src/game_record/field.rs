@@ -1,3 +1,4 @@
+use super::difficulty::Difficulty;
 pub(crate) use crate::phi_base::*;
 use bitvec::prelude::*;
 use shua_struct::field::{BinaryField, Options};
@@ -26,6 +27,21 @@ impl SongEntry {
     fn get_levels_len(&self) -> usize {
         self.unlock.iter().filter(|bit_bool| **bit_bool).count()
     }
+
+    /// 已解锁的难度、记录与 fc, 按难度顺序
+    pub fn records(&self) -> impl Iterator<Item = (Difficulty, &LevelRecord, bool)> {
+        Difficulty::ALL
+            .into_iter()
+            .filter(|d| self.unlock[d.index()])
+            .zip(self.levels.iter())
+            .map(|(d, level)| (d, level, self.fc[d.index()]))
+    }
+
+    pub fn level(&self, difficulty: Difficulty) -> Option<&LevelRecord> {
+        self.records()
+            .find(|(d, _, _)| *d == difficulty)
+            .map(|(_, level, _)| level)
+    }
 }
 #[derive(Debug, Default)]
 #[binary_struct(bit_order = Lsb0)]
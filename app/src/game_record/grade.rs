@@ -0,0 +1,144 @@
+use super::difficulty::Difficulty;
+use super::field::SongEntry;
+use super::serde::{SerializableGameRecord, SerializableLevelRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub const MAX_SCORE: u32 = 1_000_000;
+/// 达到该分数计为 clear (C)
+pub const CLEAR_SCORE: u32 = 700_000;
+
+/// 评级, 按从低到高排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Grade {
+    F,
+    C,
+    B,
+    A,
+    S,
+    V,
+    /// 蓝 V, FC 但未满分
+    VFc,
+    Phi,
+}
+
+impl Grade {
+    pub const ALL: [Grade; 8] = [
+        Grade::F,
+        Grade::C,
+        Grade::B,
+        Grade::A,
+        Grade::S,
+        Grade::V,
+        Grade::VFc,
+        Grade::Phi,
+    ];
+
+    pub fn from_score(score: u32, fc: bool) -> Self {
+        match score {
+            s if s >= MAX_SCORE => Grade::Phi,
+            _ if fc => Grade::VFc,
+            960_000.. => Grade::V,
+            920_000.. => Grade::S,
+            880_000.. => Grade::A,
+            820_000.. => Grade::B,
+            CLEAR_SCORE.. => Grade::C,
+            _ => Grade::F,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Grade::F => "F",
+            Grade::C => "C",
+            Grade::B => "B",
+            Grade::A => "A",
+            Grade::S => "S",
+            Grade::V => "V",
+            Grade::VFc => "V(FC)",
+            Grade::Phi => "φ",
+        }
+    }
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ClearState {
+    Failed,
+    Cleared,
+    FullCombo,
+    /// 满分, 即 AP
+    AllPerfect,
+}
+
+impl ClearState {
+    pub fn from_score(score: u32, fc: bool) -> Self {
+        if score >= MAX_SCORE {
+            ClearState::AllPerfect
+        } else if fc {
+            ClearState::FullCombo
+        } else if score >= CLEAR_SCORE {
+            ClearState::Cleared
+        } else {
+            ClearState::Failed
+        }
+    }
+
+    pub fn is_cleared(self) -> bool {
+        self != ClearState::Failed
+    }
+
+    pub fn is_full_combo(self) -> bool {
+        self >= ClearState::FullCombo
+    }
+}
+
+impl SerializableLevelRecord {
+    pub fn grade(&self) -> Grade {
+        Grade::from_score(self.score, self.fc)
+    }
+
+    pub fn clear_state(&self) -> ClearState {
+        ClearState::from_score(self.score, self.fc)
+    }
+}
+
+impl SongEntry {
+    pub fn grade(&self, difficulty: Difficulty) -> Option<Grade> {
+        let level = self.level(difficulty)?;
+        Some(Grade::from_score(level.score, self.fc[difficulty.index()]))
+    }
+
+    pub fn clear_state(&self, difficulty: Difficulty) -> Option<ClearState> {
+        let level = self.level(difficulty)?;
+        Some(ClearState::from_score(
+            level.score,
+            self.fc[difficulty.index()],
+        ))
+    }
+}
+
+/// 难度 -> 评级 -> 谱面数
+pub type GradeDistribution = BTreeMap<Difficulty, BTreeMap<Grade, u32>>;
+
+impl SerializableGameRecord {
+    pub fn grade_distribution(&self) -> GradeDistribution {
+        let mut dist = GradeDistribution::new();
+        for levels in self.0.values() {
+            for (diff, record) in levels {
+                *dist
+                    .entry(*diff)
+                    .or_default()
+                    .entry(record.grade())
+                    .or_default() += 1;
+            }
+        }
+        dist
+    }
+}
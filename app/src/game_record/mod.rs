@@ -1,2 +1,6 @@
+pub mod anomaly;
+pub mod difficulty;
 pub mod field;
+pub mod grade;
 pub mod serde;
+pub mod song_id;
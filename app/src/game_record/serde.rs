@@ -1,40 +1,62 @@
+use super::difficulty::Difficulty;
 use super::field::{GameRecord, LevelRecord, SongEntry};
+use super::song_id::SongId;
 use crate::phi_base::*;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
-static DIFF_ORDER: [&str; 5] = ["EZ", "HD", "IN", "AT", "Legacy"];
-
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SerializableLevelRecord {
     pub score: u32,
     pub acc: f32,
     pub fc: bool,
 }
-pub type SerializableSongRecord = BTreeMap<String, SerializableLevelRecord>;
-#[derive(Serialize, Deserialize)]
-pub struct SerializableGameRecord(BTreeMap<String, SerializableSongRecord>);
+pub type SerializableSongRecord = BTreeMap<Difficulty, SerializableLevelRecord>;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SerializableGameRecord(pub BTreeMap<String, SerializableSongRecord>);
+
+impl SerializableGameRecord {
+    pub fn get(&self, id: &SongId) -> Option<&SerializableSongRecord> {
+        self.0.get(&id.to_string())
+    }
+
+    pub fn get_mut(&mut self, id: &SongId) -> Option<&mut SerializableSongRecord> {
+        self.0.get_mut(&id.to_string())
+    }
+
+    /// 键可解析为 [`SongId`] 的歌曲, 其余的键见 [`Self::invalid_keys`]
+    pub fn songs(&self) -> impl Iterator<Item = (SongId, &SerializableSongRecord)> {
+        self.0
+            .iter()
+            .filter_map(|(name, song)| Some((name.parse().ok()?, song)))
+    }
+
+    /// 无法解析为 [`SongId`] 的键及原因
+    pub fn invalid_keys(&self) -> Vec<(&str, String)> {
+        self.0
+            .keys()
+            .filter_map(|name| Some((name.as_str(), name.parse::<SongId>().err()?)))
+            .collect()
+    }
+}
 
 impl From<GameRecord> for SerializableGameRecord {
     fn from(gr: GameRecord) -> Self {
         let mut map: BTreeMap<String, SerializableSongRecord> = BTreeMap::new();
         for song in gr.song_list {
-            let mut song_map: BTreeMap<String, SerializableLevelRecord> = BTreeMap::new();
-            let mut level_idx = 0;
-            for i in 0..5 {
-                if song.unlock[i] {
-                    let level = &song.levels[level_idx];
-                    song_map.insert(
-                        DIFF_ORDER[i].to_string(),
-                        SerializableLevelRecord {
-                            score: level.score,
-                            acc: level.acc,
-                            fc: song.fc[i],
-                        },
-                    );
-                    level_idx += 1;
-                }
-            }
+            let song_map = song
+                .records()
+                .map(|(diff, level, fc)| {
+                    let record = SerializableLevelRecord {
+                        score: level.score,
+                        acc: level.acc,
+                        fc,
+                    };
+                    (diff, record)
+                })
+                .collect();
             map.insert(song.name.0, song_map);
         }
         SerializableGameRecord(map)
@@ -47,15 +69,14 @@ impl From<SerializableGameRecord> for GameRecord {
             let mut unlock = [false; 5];
             let mut fc = [false; 5];
             let mut levels: Vec<LevelRecord> = Vec::new();
-            for (i, diff) in DIFF_ORDER.iter().enumerate() {
-                if let Some(rec) = song_map.get(*diff) {
-                    unlock[i] = true;
-                    fc[i] = rec.fc;
-                    levels.push(LevelRecord {
-                        score: rec.score,
-                        acc: rec.acc,
-                    });
-                }
+            // BTreeMap 按难度顺序遍历, 与 levels 的排列一致
+            for (diff, rec) in song_map {
+                unlock[diff.index()] = true;
+                fc[diff.index()] = rec.fc;
+                levels.push(LevelRecord {
+                    score: rec.score,
+                    acc: rec.acc,
+                });
             }
             song_list.push(SongEntry {
                 name: PhiString(name),
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// 记录键, 形如 `Glaciaxion.SunsetRay.0`
+///
+/// 最后一段为数字后缀, 倒数第二段为曲师, 其余部分为曲名 (曲名可以包含 `.`)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SongId {
+    pub title: String,
+    pub composer: String,
+    pub suffix: u32,
+}
+
+impl SongId {
+    pub fn new(title: impl Into<String>, composer: impl Into<String>, suffix: u32) -> Self {
+        SongId {
+            title: title.into(),
+            composer: composer.into(),
+            suffix,
+        }
+    }
+}
+
+impl FromStr for SongId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let err = |msg: &str| format!("SongId parse error: {}: {:?}", msg, s);
+
+        let (rest, suffix) = s.rsplit_once('.').ok_or_else(|| err("missing suffix"))?;
+        // 只接受规范写法, 保证原样还原
+        if suffix.is_empty()
+            || !suffix.bytes().all(|b| b.is_ascii_digit())
+            || (suffix.len() > 1 && suffix.starts_with('0'))
+        {
+            return Err(err("invalid suffix"));
+        }
+        let suffix = suffix.parse::<u32>().map_err(|_| err("invalid suffix"))?;
+
+        let (title, composer) = rest
+            .rsplit_once('.')
+            .ok_or_else(|| err("missing composer"))?;
+        if title.is_empty() {
+            return Err(err("empty title"));
+        }
+        if composer.is_empty() {
+            return Err(err("empty composer"));
+        }
+        Ok(SongId::new(title, composer, suffix))
+    }
+}
+
+impl fmt::Display for SongId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.title, self.composer, self.suffix)
+    }
+}
+
+impl TryFrom<&str> for SongId {
+    type Error = String;
+    fn try_from(s: &str) -> Result<Self, String> {
+        s.parse()
+    }
+}
+
+impl From<SongId> for String {
+    fn from(id: SongId) -> Self {
+        id.to_string()
+    }
+}
+
+impl Serialize for SongId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SongId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
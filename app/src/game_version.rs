@@ -0,0 +1,192 @@
+use crate::rks::RksRule;
+use crate::save::EntryKind;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+const BUILTIN: &str = include_str!("../data/game_versions.tsv");
+
+/// `Summary.game_version` 中的内部版本号
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct GameVersion(pub u16);
+
+impl From<u16> for GameVersion {
+    fn from(code: u16) -> Self {
+        GameVersion(code)
+    }
+}
+
+impl From<GameVersion> for u16 {
+    fn from(version: GameVersion) -> Self {
+        version.0
+    }
+}
+
+impl fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Release {
+    pub code: GameVersion,
+    /// 如 `3.9.0`
+    pub version: String,
+    /// `YYYY-MM-DD`
+    pub date: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub song_update_info: Option<u16>,
+    /// 该版本起变化的文件版本前缀, 如 `gameKey` -> 3, 未列出的沿用之前的版本
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub layouts: BTreeMap<String, u8>,
+}
+
+impl Release {
+    /// 按数字比较的版本号, 必须是 `主.次.修订` 三段数字
+    pub fn semver(&self) -> Result<[u32; 3], String> {
+        let err = || format!("Game version error: invalid version {:?}", self.version);
+        let parts: Vec<u32> = self
+            .version
+            .split('.')
+            .map(|part| part.parse().map_err(|_| err()))
+            .collect::<Result<_, _>>()?;
+        parts.try_into().map_err(|_| err())
+    }
+
+    /// 3.0.0 起 rks 为 best 27 + φ 3, 之前为 best 19 + φ 1
+    pub fn rks_rule(&self) -> Result<RksRule, String> {
+        Ok(if self.semver()? >= [3, 0, 0] {
+            RksRule::CURRENT
+        } else {
+            RksRule::LEGACY
+        })
+    }
+}
+
+/// 内部版本号 -> 发布版本, 内置表来自 `data/game_versions.tsv`
+///
+/// 插入时校验版本号, 同时记下该版本的 rks 规则
+#[derive(Debug, Clone, Default)]
+pub struct GameVersionTable(BTreeMap<GameVersion, (Release, RksRule)>);
+
+impl GameVersionTable {
+    pub fn builtin() -> Result<Self, String> {
+        GameVersionTable::from_tsv(BUILTIN)
+    }
+
+    pub fn from_tsv(text: &str) -> Result<Self, String> {
+        let mut table = GameVersionTable::default();
+        table.extend_tsv(text)?;
+        Ok(table)
+    }
+
+    /// 追加或覆盖条目
+    pub fn extend_tsv(&mut self, text: &str) -> Result<(), String> {
+        for (line_no, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |e: String| format!("Game version error: line {}: {}", line_no + 1, e);
+            let cols: Vec<&str> = line.split('\t').map(str::trim).collect();
+            if cols.len() < 3 {
+                return Err(err("expected at least 3 columns".to_string()));
+            }
+            let code = cols[0].parse().map_err(|e| err(format!("{}", e)))?;
+            let song_update_info = match cols.get(3) {
+                Some(col) if !col.is_empty() => {
+                    Some(col.parse().map_err(|e| err(format!("{}", e)))?)
+                }
+                _ => None,
+            };
+            let layouts = match cols.get(4) {
+                Some(col) if !col.is_empty() => parse_layouts(col).map_err(err)?,
+                _ => BTreeMap::new(),
+            };
+            let release = Release {
+                code: GameVersion(code),
+                version: cols[1].to_string(),
+                date: cols[2].to_string(),
+                song_update_info,
+                layouts,
+            };
+            if release.semver().is_err() {
+                return Err(err(format!("invalid version {:?}", release.version)));
+            }
+            self.insert(release)?;
+        }
+        Ok(())
+    }
+
+    /// 版本号无法解析时报错
+    pub fn insert(&mut self, release: Release) -> Result<(), String> {
+        let rule = release.rks_rule()?;
+        self.0.insert(release.code, (release, rule));
+        Ok(())
+    }
+
+    fn entry(&self, version: GameVersion) -> Option<&(Release, RksRule)> {
+        self.0.range(..=version).next_back().map(|(_, entry)| entry)
+    }
+
+    /// 不高于 `version` 的最近一次发布, 表中未收录的小版本归到前一个版本
+    pub fn release(&self, version: GameVersion) -> Option<&Release> {
+        self.entry(version).map(|(release, _)| release)
+    }
+
+    pub fn by_song_update(&self, song_update_info: u16) -> Option<&Release> {
+        self.releases()
+            .rfind(|r| r.song_update_info.is_some_and(|n| n <= song_update_info))
+    }
+
+    /// `version` 时 `kind` 对应文件的版本前缀, 取不高于它的最近一次变化
+    pub fn layout(&self, version: GameVersion, kind: EntryKind) -> Option<u8> {
+        let name = kind.file_name()?;
+        self.0
+            .range(..=version)
+            .rev()
+            .find_map(|(_, (release, _))| release.layouts.get(name).copied())
+    }
+
+    /// 早于表中所有版本或表为空时按当前规则计算
+    pub fn rks_rule(&self, version: GameVersion) -> RksRule {
+        self.entry(version)
+            .map_or(RksRule::CURRENT, |(_, rule)| *rule)
+    }
+
+    pub fn releases(&self) -> impl DoubleEndedIterator<Item = &Release> {
+        self.0.values().map(|(release, _)| release)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// `gameKey=3,gameProgress=4`
+fn parse_layouts(col: &str) -> Result<BTreeMap<String, u8>, String> {
+    let mut layouts = BTreeMap::new();
+    for item in col.split(',').map(str::trim) {
+        let (name, version) = item
+            .split_once('=')
+            .ok_or_else(|| format!("invalid layout {:?}", item))?;
+        if !EntryKind::ALL
+            .iter()
+            .any(|kind| kind.file_name() == Some(name))
+        {
+            return Err(format!("unknown file {:?}", name));
+        }
+        let version = version.parse().map_err(|e| format!("{}: {}", name, e))?;
+        layouts.insert(name.to_string(), version);
+    }
+    Ok(layouts)
+}
@@ -1,3 +1,4 @@
+pub mod abi;
 pub mod phi_base;
 
 pub mod game_key;
@@ -7,8 +8,32 @@ pub mod settings;
 pub mod summary;
 pub mod user;
 
+pub mod catalog;
+pub mod challenge_rank;
+pub mod game_version;
+pub mod rks;
+pub mod save;
+
+#[cfg(feature = "cloud")]
+pub mod cloud;
+
+#[cfg(feature = "schema")]
+pub mod schema;
+
+#[cfg(feature = "storage")]
+pub mod storage;
+
 #[cfg(test)]
 mod test;
 
 #[cfg(feature = "c_abi")]
 mod c_api;
+
+#[cfg(feature = "wasm")]
+mod wasm_api;
+
+#[cfg(all(feature = "component", target_arch = "wasm32"))]
+mod component;
+
+/// 编解码库的版本号
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,292 @@
+use crate::game_record::difficulty::Difficulty;
+use crate::game_record::grade::MAX_SCORE;
+use crate::game_record::serde::SerializableGameRecord;
+use crate::game_record::song_id::SongId;
+use crate::game_version::{GameVersion, GameVersionTable};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+pub const BEST_COUNT: usize = 27;
+pub const PHI_COUNT: usize = 3;
+
+/// 计入 rks 的谱面数, 随游戏版本变化, 见 [`crate::game_version::Release::rks_rule`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RksRule {
+    pub best: usize,
+    pub phi: usize,
+}
+
+impl RksRule {
+    pub const CURRENT: RksRule = RksRule {
+        best: BEST_COUNT,
+        phi: PHI_COUNT,
+    };
+    pub const LEGACY: RksRule = RksRule { best: 19, phi: 1 };
+}
+
+/// 定数表, 每首歌对应 EZ/HD/IN/AT 四个难度的定数
+#[derive(Debug, Clone, Default)]
+pub struct DifficultyTable(BTreeMap<SongId, [Option<f32>; 4]>);
+
+impl DifficultyTable {
+    /// 每行 `歌曲ID\tEZ\tHD\tIN[\tAT]`
+    pub fn from_tsv(text: &str) -> Result<Self, String> {
+        let mut table = DifficultyTable::default();
+        for (line_no, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut cols = line.split('\t');
+            let song: SongId = cols
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .parse()
+                .map_err(|e| format!("Difficulty table error: line {}: {}", line_no + 1, e))?;
+            let mut constants = [None; 4];
+            for (i, col) in cols.take(4).enumerate() {
+                let col = col.trim();
+                if col.is_empty() {
+                    continue;
+                }
+                let value = col
+                    .parse::<f32>()
+                    .map_err(|e| format!("Difficulty table error: line {}: {}", line_no + 1, e))?;
+                constants[i] = Some(value);
+            }
+            table.insert(song, constants);
+        }
+        Ok(table)
+    }
+
+    pub fn insert(&mut self, song: SongId, constants: [Option<f32>; 4]) {
+        self.0.insert(song, constants);
+    }
+
+    pub fn constant(&self, song: &SongId, difficulty: Difficulty) -> Option<f32> {
+        if !difficulty.is_rated() {
+            return None;
+        }
+        self.0.get(song)?[difficulty.index()]
+    }
+
+    /// 所有有定数的谱面
+    pub fn charts(&self) -> impl Iterator<Item = (&SongId, Difficulty, f32)> {
+        self.0.iter().flat_map(|(song, constants)| {
+            Difficulty::RATED
+                .into_iter()
+                .filter_map(move |diff| Some((song, diff, constants[diff.index()]?)))
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+pub fn chart_rks(acc: f32, constant: f32) -> f32 {
+    if acc < 70.0 {
+        return 0.0;
+    }
+    ((acc - 55.0) / 45.0).powi(2) * constant
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChartRks {
+    pub song: SongId,
+    pub difficulty: Difficulty,
+    pub constant: f32,
+    pub score: u32,
+    pub acc: f32,
+    pub fc: bool,
+    pub rks: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RksResult {
+    pub rks: f32,
+    pub best: Vec<ChartRks>,
+    pub phi: Vec<ChartRks>,
+    /// 无法解析为 [`SongId`] 而未参与计算的记录键
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<String>,
+}
+
+/// 所有有定数的谱面, 按单曲 rks 降序
+pub fn charts(record: &SerializableGameRecord, table: &DifficultyTable) -> Vec<ChartRks> {
+    let mut charts: Vec<ChartRks> = record
+        .songs()
+        .flat_map(|(song, levels)| {
+            levels.iter().filter_map(move |(diff, level)| {
+                let constant = table.constant(&song, *diff)?;
+                Some(ChartRks {
+                    song: song.clone(),
+                    difficulty: *diff,
+                    constant,
+                    score: level.score,
+                    acc: level.acc,
+                    fc: level.fc,
+                    rks: chart_rks(level.acc, constant),
+                })
+            })
+        })
+        .collect();
+    charts.sort_by(|a, b| b.rks.total_cmp(&a.rks));
+    charts
+}
+
+/// 按存档的游戏版本选择规则, 3.0.0 起 rks = (最好 27 个谱面 + 最好 3 个 φ 谱面) / 30
+pub fn compute(
+    record: &SerializableGameRecord,
+    table: &DifficultyTable,
+    versions: &GameVersionTable,
+    version: GameVersion,
+) -> RksResult {
+    compute_with(record, table, versions.rks_rule(version))
+}
+
+pub fn compute_with(
+    record: &SerializableGameRecord,
+    table: &DifficultyTable,
+    rule: RksRule,
+) -> RksResult {
+    let mut result = summarize(charts(record, table), rule);
+    result.skipped = record
+        .invalid_keys()
+        .into_iter()
+        .map(|(name, _)| name.to_string())
+        .collect();
+    result
+}
+
+/// `charts` 需按单曲 rks 降序
+fn summarize(charts: Vec<ChartRks>, rule: RksRule) -> RksResult {
+    let phi: Vec<ChartRks> = charts
+        .iter()
+        .filter(|c| c.score >= MAX_SCORE)
+        .take(rule.phi)
+        .cloned()
+        .collect();
+    let best: Vec<ChartRks> = charts.into_iter().take(rule.best).collect();
+
+    let sum: f32 = best.iter().chain(phi.iter()).map(|c| c.rks).sum();
+    RksResult {
+        rks: sum / (rule.best + rule.phi) as f32,
+        best,
+        phi,
+        skipped: Vec::new(),
+    }
+}
+
+/// [`chart_rks`] 的反函数, 达到 `rks` 所需的最低 acc, 超过定数时无法达到
+pub fn chart_acc(rks: f32, constant: f32) -> Option<f32> {
+    if constant <= 0.0 || rks > constant {
+        return None;
+    }
+    Some((55.0 + 45.0 * (rks.max(0.0) / constant).sqrt()).max(70.0))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PushTarget {
+    pub chart: ChartRks,
+    /// 进入 best 列表所需 acc, 已在列表中时为 None
+    pub enter_best: Option<f32>,
+    /// 总 rks 提高 0.01 所需 acc, 无法达到时为 None
+    pub gain: Option<f32>,
+    /// AP 后总 rks 的增量, 包括进入 φ 列表的部分
+    pub ap_gain: f32,
+}
+
+impl PushTarget {
+    /// 达到 +0.01 需要提高的 acc
+    pub fn cost(&self) -> Option<f32> {
+        Some(self.gain? - self.chart.acc)
+    }
+}
+
+/// 每个有定数的谱面 (包括未游玩的) 的推分目标, 按所需提高的 acc 升序,
+/// 无法 +0.01 的谱面排在最后并按 AP 增量降序
+pub fn recommend(
+    record: &SerializableGameRecord,
+    table: &DifficultyTable,
+    rule: RksRule,
+) -> Vec<PushTarget> {
+    let mut all = charts(record, table);
+    for (song, difficulty, constant) in table.charts() {
+        if record
+            .get(song)
+            .is_some_and(|levels| levels.contains_key(&difficulty))
+        {
+            continue;
+        }
+        all.push(ChartRks {
+            song: song.clone(),
+            difficulty,
+            constant,
+            score: 0,
+            acc: 0.0,
+            fc: false,
+            rks: 0.0,
+        });
+    }
+    all.sort_by(|a, b| b.rks.total_cmp(&a.rks));
+
+    let current = summarize(all.clone(), rule);
+    let floor = (rule.best > 0 && all.len() >= rule.best).then(|| all[rule.best - 1].rks);
+    // φ 列表未满时新的 AP 谱面直接加入, 否则替换最低的一个
+    let phi_floor = if current.phi.len() < rule.phi {
+        Some(0.0)
+    } else {
+        current.phi.last().map(|c| c.rks)
+    };
+    let step = 0.01 * (rule.best + rule.phi) as f32;
+
+    let mut targets: Vec<PushTarget> = all
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.score < MAX_SCORE)
+        .map(|(i, chart)| {
+            let in_best = i < rule.best;
+            let enter_best = match (in_best, floor) {
+                (true, _) => None,
+                (false, Some(floor)) => chart_acc(floor, chart.constant),
+                (false, None) => Some(70.0),
+            };
+            // 在 best 中时直接增加, 否则替换最后一名
+            let base = if in_best {
+                chart.rks
+            } else {
+                floor.unwrap_or(0.0)
+            };
+            let gain = chart_acc(base + step, chart.constant);
+
+            // AP 后单曲 rks 变为定数, 只影响 best 与 φ 各一个位置
+            let best_gain = if in_best {
+                chart.constant - chart.rks
+            } else {
+                (chart.constant - base).max(0.0)
+            };
+            let phi_gain = phi_floor.map_or(0.0, |floor| (chart.constant - floor).max(0.0));
+            let ap_gain = (best_gain + phi_gain) / (rule.best + rule.phi) as f32;
+
+            PushTarget {
+                chart: chart.clone(),
+                enter_best,
+                gain,
+                ap_gain,
+            }
+        })
+        .collect();
+
+    targets.sort_by(|a, b| match (a.cost(), b.cost()) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => b.ap_gain.total_cmp(&a.ap_gain),
+    });
+    targets
+}
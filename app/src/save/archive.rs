@@ -0,0 +1,107 @@
+use super::{EntryKind, PhiSave, SaveEntry};
+use crate::game_version::GameVersionTable;
+use crate::summary::serde::SerializableSummary;
+use aes::Aes256;
+use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit, block_padding::Pkcs7};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::io::{Cursor, Read, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const KEY: &str = "6Jaa0qVAJZuXkZCLiOa/Ax5tIZVu+taKUN1V1nqwkks=";
+const IV: &str = "Kk/wisgNYwcAV8WVGMgyUw==";
+
+fn key_iv() -> ([u8; 32], [u8; 16]) {
+    let mut key = [0u8; 32];
+    let mut iv = [0u8; 16];
+    key.copy_from_slice(&STANDARD.decode(KEY).unwrap());
+    iv.copy_from_slice(&STANDARD.decode(IV).unwrap());
+    (key, iv)
+}
+
+pub fn decrypt(data: &[u8]) -> Result<Vec<u8>, String> {
+    let (key, iv) = key_iv();
+    cbc::Decryptor::<Aes256>::new(&key.into(), &iv.into())
+        .decrypt_padded_vec_mut::<Pkcs7>(data)
+        .map_err(|e| format!("Decrypt error: {}", e))
+}
+
+pub fn encrypt(data: &[u8]) -> Vec<u8> {
+    let (key, iv) = key_iv();
+    cbc::Encryptor::<Aes256>::new(&key.into(), &iv.into()).encrypt_padded_vec_mut::<Pkcs7>(data)
+}
+
+pub fn decode_summary(summary: &str) -> Result<SerializableSummary, String> {
+    let bytes = STANDARD
+        .decode(summary)
+        .map_err(|e| format!("Summary decode error: {}", e))?;
+    match SaveEntry::parse(EntryKind::Summary, &bytes)? {
+        SaveEntry::Summary(s) => Ok(s),
+        _ => unreachable!(),
+    }
+}
+
+pub fn encode_summary(summary: &SerializableSummary) -> Result<String, String> {
+    let bytes = SaveEntry::Summary(summary.clone()).build()?;
+    Ok(STANDARD.encode(bytes))
+}
+
+impl PhiSave {
+    pub fn from_archive(bytes: &[u8]) -> Result<Self, String> {
+        let mut zip =
+            ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Archive error: {}", e))?;
+        let mut entries = Vec::new();
+        let mut versions = std::collections::BTreeMap::new();
+
+        for kind in EntryKind::ALL {
+            let Some(name) = kind.file_name() else {
+                continue;
+            };
+            let mut file = zip
+                .by_name(name)
+                .map_err(|e| format!("Archive error: {}: {}", name, e))?;
+            let mut data = Vec::new();
+            file.read_to_end(&mut data)
+                .map_err(|e| format!("Archive error: {}: {}", name, e))?;
+
+            let (version, encrypted) = data
+                .split_first()
+                .ok_or_else(|| format!("Archive error: {} is empty", name))?;
+            versions.insert(name.to_string(), *version);
+            entries.push(SaveEntry::parse(kind, &decrypt(encrypted)?)?);
+        }
+
+        let mut save = PhiSave::from_entries(entries)?;
+        save.versions = versions;
+        Ok(save)
+    }
+
+    pub fn to_archive(&self) -> Result<Vec<u8>, String> {
+        self.write_archive(|kind| self.version(kind))
+    }
+
+    /// 按 [`PhiSave::layout_version`] 选择各文件的版本前缀
+    pub fn to_archive_for(&self, versions: &GameVersionTable) -> Result<Vec<u8>, String> {
+        self.write_archive(|kind| self.layout_version(kind, versions))
+    }
+
+    fn write_archive(&self, version: impl Fn(EntryKind) -> u8) -> Result<Vec<u8>, String> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        for entry in self.file_entries() {
+            let kind = entry.kind();
+            let name = kind.file_name().unwrap();
+            let mut data = vec![version(kind)];
+            data.extend(encrypt(&entry.build()?));
+
+            zip.start_file(name, options)
+                .and_then(|_| Ok(zip.write_all(&data)?))
+                .map_err(|e| format!("Archive error: {}: {}", name, e))?;
+        }
+
+        let cursor = zip.finish().map_err(|e| format!("Archive error: {}", e))?;
+        Ok(cursor.into_inner())
+    }
+}
@@ -0,0 +1,291 @@
+use super::{EntryKind, PhiSave};
+use crate::game_key::serde::SerializableKey;
+use crate::game_record::difficulty::Difficulty;
+use crate::game_record::serde::{
+    SerializableGameRecord, SerializableLevelRecord, SerializableSongRecord,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    SongAdded {
+        song: String,
+        charts: SerializableSongRecord,
+    },
+    SongRemoved {
+        song: String,
+        charts: SerializableSongRecord,
+    },
+    ChartAdded {
+        song: String,
+        difficulty: Difficulty,
+        after: SerializableLevelRecord,
+    },
+    ChartRemoved {
+        song: String,
+        difficulty: Difficulty,
+        before: SerializableLevelRecord,
+    },
+    ChartChanged {
+        song: String,
+        difficulty: Difficulty,
+        before: SerializableLevelRecord,
+        after: SerializableLevelRecord,
+    },
+    KeyAdded {
+        key: SerializableKey,
+    },
+    KeyRemoved {
+        key: SerializableKey,
+    },
+    KeyChanged {
+        before: SerializableKey,
+        after: SerializableKey,
+    },
+    /// 其余字段, path 形如 `game_progress.money.kib`
+    Field {
+        path: String,
+        before: Value,
+        after: Value,
+    },
+}
+
+/// 两份存档的逐字段差异, `Display` 输出每行一条的文本
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct SaveDiff {
+    pub changes: Vec<Change>,
+}
+
+impl SaveDiff {
+    pub fn between(old: &PhiSave, new: &PhiSave) -> Result<Self, String> {
+        let mut changes = diff_records(&old.game_record, &new.game_record);
+        diff_keys(&old.game_key.keys, &new.game_key.keys, &mut changes);
+
+        let entries = [
+            (EntryKind::User, to_value(&old.user)?, to_value(&new.user)?),
+            (
+                EntryKind::Summary,
+                to_value(&old.summary)?,
+                to_value(&new.summary)?,
+            ),
+            (
+                EntryKind::GameProgress,
+                to_value(&old.game_progress)?,
+                to_value(&new.game_progress)?,
+            ),
+            (
+                EntryKind::GameKey,
+                without_key_list(to_value(&old.game_key)?),
+                without_key_list(to_value(&new.game_key)?),
+            ),
+            (
+                EntryKind::Settings,
+                to_value(&old.settings)?,
+                to_value(&new.settings)?,
+            ),
+        ];
+        for (kind, before, after) in entries {
+            diff_value(kind.name().to_string(), before, after, &mut changes);
+        }
+        Ok(SaveDiff { changes })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Diff error: {}", e))
+}
+
+/// key_list 按名称单独比较
+fn without_key_list(mut value: Value) -> Value {
+    if let Value::Object(map) = &mut value {
+        map.remove("key_list");
+    }
+    value
+}
+
+fn diff_records(old: &SerializableGameRecord, new: &SerializableGameRecord) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for (song, before) in &old.0 {
+        let Some(after) = new.0.get(song) else {
+            changes.push(Change::SongRemoved {
+                song: song.clone(),
+                charts: before.clone(),
+            });
+            continue;
+        };
+        for (diff, b) in before {
+            match after.get(diff) {
+                None => changes.push(Change::ChartRemoved {
+                    song: song.clone(),
+                    difficulty: *diff,
+                    before: b.clone(),
+                }),
+                Some(a)
+                    if a.score != b.score || a.acc.to_bits() != b.acc.to_bits() || a.fc != b.fc =>
+                {
+                    changes.push(Change::ChartChanged {
+                        song: song.clone(),
+                        difficulty: *diff,
+                        before: b.clone(),
+                        after: a.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        for (diff, a) in after {
+            if !before.contains_key(diff) {
+                changes.push(Change::ChartAdded {
+                    song: song.clone(),
+                    difficulty: *diff,
+                    after: a.clone(),
+                });
+            }
+        }
+    }
+    for (song, after) in &new.0 {
+        if !old.0.contains_key(song) {
+            changes.push(Change::SongAdded {
+                song: song.clone(),
+                charts: after.clone(),
+            });
+        }
+    }
+    changes
+}
+
+fn diff_keys(old: &[SerializableKey], new: &[SerializableKey], changes: &mut Vec<Change>) {
+    for before in old {
+        match new.iter().find(|k| k.name == before.name) {
+            None => changes.push(Change::KeyRemoved {
+                key: before.clone(),
+            }),
+            Some(after) if after.ktype != before.ktype || after.flag != before.flag => changes
+                .push(Change::KeyChanged {
+                    before: before.clone(),
+                    after: after.clone(),
+                }),
+            Some(_) => {}
+        }
+    }
+    for after in new {
+        if !old.iter().any(|k| k.name == after.name) {
+            changes.push(Change::KeyAdded { key: after.clone() });
+        }
+    }
+}
+
+/// 对象逐键比较, 等长数组逐项比较, 其余整体比较
+fn diff_value(path: String, before: Value, after: Value, changes: &mut Vec<Change>) {
+    match (before, after) {
+        (Value::Object(mut b), Value::Object(mut a)) => {
+            let mut keys: Vec<String> = b.keys().chain(a.keys()).cloned().collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let before = b.remove(&key).unwrap_or(Value::Null);
+                let after = a.remove(&key).unwrap_or(Value::Null);
+                diff_value(format!("{}.{}", path, key), before, after, changes);
+            }
+        }
+        (Value::Array(b), Value::Array(a)) if b.len() == a.len() => {
+            for (i, (before, after)) in b.into_iter().zip(a).enumerate() {
+                diff_value(format!("{}[{}]", path, i), before, after, changes);
+            }
+        }
+        (before, after) if before != after => changes.push(Change::Field {
+            path,
+            before,
+            after,
+        }),
+        _ => {}
+    }
+}
+
+struct Level<'a>(&'a SerializableLevelRecord);
+
+impl fmt::Display for Level<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:.2}%", self.0.score, self.0.acc)?;
+        if self.0.fc {
+            f.write_str(" FC")?;
+        }
+        Ok(())
+    }
+}
+
+fn flags(flags: &[bool]) -> String {
+    flags.iter().map(|&f| if f { '1' } else { '0' }).collect()
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let diffs = |charts: &SerializableSongRecord| {
+            charts
+                .keys()
+                .map(|d| d.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            Change::SongAdded { song, charts } => write!(f, "+ {} ({})", song, diffs(charts)),
+            Change::SongRemoved { song, charts } => write!(f, "- {} ({})", song, diffs(charts)),
+            Change::ChartAdded {
+                song,
+                difficulty,
+                after,
+            } => write!(f, "+ {} {}: {}", song, difficulty, Level(after)),
+            Change::ChartRemoved {
+                song,
+                difficulty,
+                before,
+            } => write!(f, "- {} {}: {}", song, difficulty, Level(before)),
+            Change::ChartChanged {
+                song,
+                difficulty,
+                before,
+                after,
+            } => write!(
+                f,
+                "~ {} {}: {} -> {}",
+                song,
+                difficulty,
+                Level(before),
+                Level(after)
+            ),
+            Change::KeyAdded { key } => write!(f, "+ key {}", key.name),
+            Change::KeyRemoved { key } => write!(f, "- key {}", key.name),
+            Change::KeyChanged { before, after } => write!(
+                f,
+                "~ key {}: type {} -> {}, flag {:?} -> {:?}",
+                after.name,
+                flags(&before.ktype),
+                flags(&after.ktype),
+                before.flag,
+                after.flag
+            ),
+            Change::Field {
+                path,
+                before,
+                after,
+            } => write!(f, "~ {}: {} -> {}", path, before, after),
+        }
+    }
+}
+
+impl fmt::Display for SaveDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use crate::game_key::{field::GameKey, serde::SerializableGameKey};
+use crate::game_progress::{field::GameProgress, serde::SerializableGameProgress};
+use crate::game_record::{field::GameRecord, serde::SerializableGameRecord};
+use crate::settings::{field::Settings, serde::SerializableSettings};
+use crate::summary::{field::Summary, serde::SerializableSummary};
+use crate::user::{field::User, serde::SerializableUser};
+use bitvec::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use shua_struct::field::BinaryField;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EntryKind {
+    User,
+    Summary,
+    GameRecord,
+    GameProgress,
+    GameKey,
+    Settings,
+}
+
+impl EntryKind {
+    pub const ALL: [EntryKind; 6] = [
+        EntryKind::User,
+        EntryKind::Summary,
+        EntryKind::GameRecord,
+        EntryKind::GameProgress,
+        EntryKind::GameKey,
+        EntryKind::Settings,
+    ];
+
+    /// API 名, 如 `parse_game_record` 中的 `game_record`
+    pub fn name(self) -> &'static str {
+        match self {
+            EntryKind::User => "user",
+            EntryKind::Summary => "summary",
+            EntryKind::GameRecord => "game_record",
+            EntryKind::GameProgress => "game_progress",
+            EntryKind::GameKey => "game_key",
+            EntryKind::Settings => "settings",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.name() == name)
+    }
+
+    /// 存档压缩包内的文件名, summary 不在压缩包内
+    pub fn file_name(self) -> Option<&'static str> {
+        match self {
+            EntryKind::User => Some("user"),
+            EntryKind::Summary => None,
+            EntryKind::GameRecord => Some("gameRecord"),
+            EntryKind::GameProgress => Some("gameProgress"),
+            EntryKind::GameKey => Some("gameKey"),
+            EntryKind::Settings => Some("settings"),
+        }
+    }
+}
+
+pub enum SaveEntry {
+    User(SerializableUser),
+    Summary(SerializableSummary),
+    GameRecord(SerializableGameRecord),
+    GameProgress(SerializableGameProgress),
+    GameKey(SerializableGameKey),
+    Settings(SerializableSettings),
+}
+
+fn parse_bytes<T: BinaryField<Lsb0>>(bytes: &[u8]) -> Result<T, String> {
+    let bits = BitSlice::<u8, Lsb0>::from_slice(bytes);
+    Ok(T::parse(bits, &None)?.0)
+}
+
+fn build_bytes<T: BinaryField<Lsb0>>(item: T) -> Result<Vec<u8>, String> {
+    Ok(item.build(&None)?.into_vec())
+}
+
+impl SaveEntry {
+    pub fn kind(&self) -> EntryKind {
+        match self {
+            SaveEntry::User(_) => EntryKind::User,
+            SaveEntry::Summary(_) => EntryKind::Summary,
+            SaveEntry::GameRecord(_) => EntryKind::GameRecord,
+            SaveEntry::GameProgress(_) => EntryKind::GameProgress,
+            SaveEntry::GameKey(_) => EntryKind::GameKey,
+            SaveEntry::Settings(_) => EntryKind::Settings,
+        }
+    }
+
+    pub fn parse(kind: EntryKind, bytes: &[u8]) -> Result<Self, String> {
+        Ok(match kind {
+            EntryKind::User => SaveEntry::User(parse_bytes::<User>(bytes)?.into()),
+            EntryKind::Summary => SaveEntry::Summary(parse_bytes::<Summary>(bytes)?.into()),
+            EntryKind::GameRecord => {
+                SaveEntry::GameRecord(parse_bytes::<GameRecord>(bytes)?.into())
+            }
+            EntryKind::GameProgress => {
+                SaveEntry::GameProgress(parse_bytes::<GameProgress>(bytes)?.into())
+            }
+            EntryKind::GameKey => SaveEntry::GameKey(parse_bytes::<GameKey>(bytes)?.into()),
+            EntryKind::Settings => SaveEntry::Settings(parse_bytes::<Settings>(bytes)?.into()),
+        })
+    }
+
+    pub fn build(self) -> Result<Vec<u8>, String> {
+        match self {
+            SaveEntry::User(v) => build_bytes(User::from(v)),
+            SaveEntry::Summary(v) => build_bytes(Summary::try_from(v)?),
+            SaveEntry::GameRecord(v) => build_bytes(GameRecord::from(v)),
+            SaveEntry::GameProgress(v) => build_bytes(GameProgress::try_from(v)?),
+            SaveEntry::GameKey(v) => build_bytes(GameKey::try_from(v)?),
+            SaveEntry::Settings(v) => build_bytes(Settings::try_from(v)?),
+        }
+    }
+
+    /// 按条目类型反序列化, 格式由调用方的 Deserializer 决定
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        kind: EntryKind,
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        Ok(match kind {
+            EntryKind::User => SaveEntry::User(Deserialize::deserialize(deserializer)?),
+            EntryKind::Summary => SaveEntry::Summary(Deserialize::deserialize(deserializer)?),
+            EntryKind::GameRecord => SaveEntry::GameRecord(Deserialize::deserialize(deserializer)?),
+            EntryKind::GameProgress => {
+                SaveEntry::GameProgress(Deserialize::deserialize(deserializer)?)
+            }
+            EntryKind::GameKey => SaveEntry::GameKey(Deserialize::deserialize(deserializer)?),
+            EntryKind::Settings => SaveEntry::Settings(Deserialize::deserialize(deserializer)?),
+        })
+    }
+}
+
+impl Serialize for SaveEntry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            SaveEntry::User(v) => v.serialize(serializer),
+            SaveEntry::Summary(v) => v.serialize(serializer),
+            SaveEntry::GameRecord(v) => v.serialize(serializer),
+            SaveEntry::GameProgress(v) => v.serialize(serializer),
+            SaveEntry::GameKey(v) => v.serialize(serializer),
+            SaveEntry::Settings(v) => v.serialize(serializer),
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use super::PhiSave;
+use crate::game_key::serde::{SerializableGameKey, SerializableKey};
+use crate::game_progress::serde::SerializableGameProgress;
+use crate::game_record::difficulty::Difficulty;
+use crate::game_record::serde::{SerializableGameRecord, SerializableLevelRecord};
+use serde::{Deserialize, Serialize};
+
+/// 同一谱面两边都有记录时的合并方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartPolicy {
+    /// 整条保留分数较高的一边, score 与 acc 不会来自不同的游玩
+    #[default]
+    BestScore,
+    /// 分别取最高分, 最高 acc, fc 取或, 结果可能是没有真实出现过的组合
+    Fieldwise,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChartChange {
+    pub song: String,
+    pub difficulty: Difficulty,
+    /// None 表示原来没有该谱面
+    pub before: Option<SerializableLevelRecord>,
+    pub after: SerializableLevelRecord,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MergeReport {
+    pub charts: Vec<ChartChange>,
+    /// 新增或有变化的 key 名
+    pub keys: Vec<String>,
+    /// 有变化的 gameKey / gameProgress 字段
+    pub flags: Vec<&'static str>,
+}
+
+impl MergeReport {
+    pub fn is_empty(&self) -> bool {
+        self.charts.is_empty() && self.keys.is_empty() && self.flags.is_empty()
+    }
+}
+
+fn merge_level(
+    ours: &SerializableLevelRecord,
+    theirs: &SerializableLevelRecord,
+    policy: ChartPolicy,
+) -> SerializableLevelRecord {
+    match policy {
+        ChartPolicy::Fieldwise => SerializableLevelRecord {
+            score: ours.score.max(theirs.score),
+            acc: ours.acc.max(theirs.acc),
+            fc: ours.fc || theirs.fc,
+        },
+        ChartPolicy::BestScore if theirs.score > ours.score => theirs.clone(),
+        ChartPolicy::BestScore => ours.clone(),
+    }
+}
+
+fn same_level(a: &SerializableLevelRecord, b: &SerializableLevelRecord) -> bool {
+    a.score == b.score && a.acc.to_bits() == b.acc.to_bits() && a.fc == b.fc
+}
+
+/// 按位取或, 返回是否有变化
+fn merge_flags(ours: &mut [bool], theirs: &[bool]) -> bool {
+    let mut changed = false;
+    for (a, b) in ours.iter_mut().zip(theirs) {
+        changed |= !*a && *b;
+        *a |= *b;
+    }
+    changed
+}
+
+fn merge_key(ours: &SerializableKey, theirs: &SerializableKey) -> SerializableKey {
+    if ours.is_consistent() && theirs.is_consistent() {
+        let mut progress = ours.progress_map();
+        for (kind, count) in theirs.progress_map() {
+            let value = progress.entry(kind).or_default();
+            *value = (*value).max(count);
+        }
+        return SerializableKey::new(ours.name.clone(), &progress);
+    }
+    // flag 与类型对不上时只能按位置合并
+    let mut merged = ours.clone();
+    merge_flags(&mut merged.ktype, &theirs.ktype);
+    if merged.flag.len() < theirs.flag.len() {
+        merged.flag.resize(theirs.flag.len(), 0);
+    }
+    for (a, b) in merged.flag.iter_mut().zip(&theirs.flag) {
+        *a = (*a).max(*b);
+    }
+    merged
+}
+
+impl SerializableGameRecord {
+    /// 合并另一份记录, 对方独有的歌曲与难度直接加入, 没有任何谱面的歌曲忽略
+    pub fn merge(
+        &mut self,
+        other: &SerializableGameRecord,
+        policy: ChartPolicy,
+    ) -> Vec<ChartChange> {
+        let mut changes = Vec::new();
+        for (song, levels) in other.0.iter().filter(|(_, levels)| !levels.is_empty()) {
+            let ours = self.0.entry(song.clone()).or_default();
+            for (diff, theirs) in levels {
+                let before = ours.get(diff).cloned();
+                let after = match &before {
+                    Some(level) => merge_level(level, theirs, policy),
+                    None => theirs.clone(),
+                };
+                if before.as_ref().is_some_and(|b| same_level(b, &after)) {
+                    continue;
+                }
+                ours.insert(*diff, after.clone());
+                changes.push(ChartChange {
+                    song: song.clone(),
+                    difficulty: *diff,
+                    before,
+                    after,
+                });
+            }
+        }
+        changes
+    }
+}
+
+impl SerializableGameKey {
+    /// key 按名称合并, 类型取并集, 各类型的进度取较大值; 已读标记取或
+    pub fn merge(&mut self, other: &SerializableGameKey, report: &mut MergeReport) {
+        for theirs in &other.keys {
+            match self.keys.iter_mut().find(|k| k.name == theirs.name) {
+                Some(ours) => {
+                    let merged = merge_key(ours, theirs);
+                    if merged != *ours {
+                        *ours = merged;
+                        report.keys.push(ours.name.clone());
+                    }
+                }
+                None => {
+                    self.keys.push(theirs.clone());
+                    report.keys.push(theirs.name.clone());
+                }
+            }
+        }
+
+        let flags: [(&'static str, &mut [bool], &[bool]); 4] = [
+            (
+                "lanota_read_keys",
+                &mut self.lanota_read_keys,
+                &other.lanota_read_keys,
+            ),
+            (
+                "camellia_read_key",
+                &mut self.camellia_read_key,
+                &other.camellia_read_key,
+            ),
+            (
+                "side_story4_begin_read_key",
+                std::slice::from_mut(&mut self.side_story4_begin_read_key),
+                std::slice::from_ref(&other.side_story4_begin_read_key),
+            ),
+            (
+                "old_score_cleared_v390",
+                std::slice::from_mut(&mut self.old_score_cleared_v390),
+                std::slice::from_ref(&other.old_score_cleared_v390),
+            ),
+        ];
+        for (name, ours, theirs) in flags {
+            if merge_flags(ours, theirs) {
+                report.flags.push(name);
+            }
+        }
+    }
+}
+
+impl SerializableGameProgress {
+    /// 只合并解锁相关的数组, 其余字段保持不变
+    pub fn merge(&mut self, other: &SerializableGameProgress, report: &mut MergeReport) {
+        let ch8 = &mut self.chapter8_base;
+        let flags: [(&'static str, &mut [bool], &[bool]); 10] = [
+            (
+                "unlock_flag_of_spasmodic",
+                &mut self.unlock_flag_of_spasmodic.bits,
+                &other.unlock_flag_of_spasmodic.bits,
+            ),
+            (
+                "unlock_flag_of_igallta",
+                &mut self.unlock_flag_of_igallta.bits,
+                &other.unlock_flag_of_igallta.bits,
+            ),
+            (
+                "unlock_flag_of_rrharil",
+                &mut self.unlock_flag_of_rrharil.bits,
+                &other.unlock_flag_of_rrharil.bits,
+            ),
+            (
+                "flag_of_song_record_key",
+                &mut self.flag_of_song_record_key.bits,
+                &other.flag_of_song_record_key.bits,
+            ),
+            (
+                "random_version_unlocked",
+                &mut self.random_version_unlocked.bits,
+                &other.random_version_unlocked.bits,
+            ),
+            (
+                "chapter8_base.unlock_begin",
+                std::slice::from_mut(&mut ch8.unlock_begin),
+                std::slice::from_ref(&other.chapter8_base.unlock_begin),
+            ),
+            (
+                "chapter8_base.unlock_second_phase",
+                std::slice::from_mut(&mut ch8.unlock_second_phase),
+                std::slice::from_ref(&other.chapter8_base.unlock_second_phase),
+            ),
+            (
+                "chapter8_base.passed",
+                std::slice::from_mut(&mut ch8.passed),
+                std::slice::from_ref(&other.chapter8_base.passed),
+            ),
+            (
+                "chapter8_song_unlocked",
+                &mut self.chapter8_song_unlocked.bits,
+                &other.chapter8_song_unlocked.bits,
+            ),
+            (
+                "flag_of_song_record_key_takumi",
+                &mut self.flag_of_song_record_key_takumi.bits,
+                &other.flag_of_song_record_key_takumi.bits,
+            ),
+        ];
+        for (name, ours, theirs) in flags {
+            if merge_flags(ours, theirs) {
+                report.flags.push(name);
+            }
+        }
+    }
+}
+
+impl PhiSave {
+    /// 把另一份存档的记录与解锁合并进来, user/settings 保持不变
+    ///
+    /// summary 不会更新, 需要时调用 [`PhiSave::rebuild_summary`]
+    pub fn merge(&mut self, other: &PhiSave, policy: ChartPolicy) -> MergeReport {
+        let mut report = MergeReport {
+            charts: self.game_record.merge(&other.game_record, policy),
+            ..Default::default()
+        };
+        self.game_key.merge(&other.game_key, &mut report);
+        self.game_progress.merge(&other.game_progress, &mut report);
+        report
+    }
+}
@@ -0,0 +1,173 @@
+pub mod entry;
+pub mod merge;
+pub mod redact;
+pub mod validate;
+
+#[cfg(feature = "archive")]
+pub mod archive;
+#[cfg(feature = "diff")]
+pub mod diff;
+#[cfg(feature = "archive")]
+pub mod upload;
+
+pub use entry::{EntryKind, SaveEntry};
+pub use merge::{ChartPolicy, MergeReport};
+pub use redact::{RedactAction, RedactRules, Redaction};
+pub use validate::{Finding, Severity};
+
+use crate::game_key::serde::SerializableGameKey;
+use crate::game_progress::serde::SerializableGameProgress;
+use crate::game_record::grade::ClearState;
+use crate::game_record::serde::SerializableGameRecord;
+use crate::game_version::{GameVersion, GameVersionTable};
+use crate::rks::{self, DifficultyTable, RksRule};
+use crate::settings::serde::SerializableSettings;
+use crate::summary::serde::{SerializableLevel, SerializableMultiLevel, SerializableSummary};
+use crate::user::serde::SerializableUser;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 一份完整的云存档, summary 来自云端记录而非压缩包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhiSave {
+    pub user: SerializableUser,
+    pub game_record: SerializableGameRecord,
+    pub game_progress: SerializableGameProgress,
+    pub game_key: SerializableGameKey,
+    pub settings: SerializableSettings,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<SerializableSummary>,
+    /// 压缩包内各文件的版本前缀, 缺省时使用 [`default_version`]
+    #[serde(default)]
+    pub versions: BTreeMap<String, u8>,
+}
+
+/// 没有原 summary 时使用的存档格式版本
+pub const DEFAULT_SAVE_VERSION: u8 = 6;
+
+pub fn default_version(kind: EntryKind) -> u8 {
+    match kind {
+        EntryKind::GameKey => 3,
+        EntryKind::GameProgress => 4,
+        _ => 1,
+    }
+}
+
+impl PhiSave {
+    pub fn from_entries(entries: Vec<SaveEntry>) -> Result<Self, String> {
+        let mut user = None;
+        let mut game_record = None;
+        let mut game_progress = None;
+        let mut game_key = None;
+        let mut settings = None;
+        let mut summary = None;
+        for entry in entries {
+            match entry {
+                SaveEntry::User(v) => user = Some(v),
+                SaveEntry::Summary(v) => summary = Some(v),
+                SaveEntry::GameRecord(v) => game_record = Some(v),
+                SaveEntry::GameProgress(v) => game_progress = Some(v),
+                SaveEntry::GameKey(v) => game_key = Some(v),
+                SaveEntry::Settings(v) => settings = Some(v),
+            }
+        }
+        let missing = |kind: EntryKind| format!("Save error: missing {}", kind.name());
+        Ok(PhiSave {
+            user: user.ok_or_else(|| missing(EntryKind::User))?,
+            game_record: game_record.ok_or_else(|| missing(EntryKind::GameRecord))?,
+            game_progress: game_progress.ok_or_else(|| missing(EntryKind::GameProgress))?,
+            game_key: game_key.ok_or_else(|| missing(EntryKind::GameKey))?,
+            settings: settings.ok_or_else(|| missing(EntryKind::Settings))?,
+            summary,
+            versions: BTreeMap::new(),
+        })
+    }
+
+    /// 压缩包内的条目, 不含 summary
+    pub fn file_entries(&self) -> Vec<SaveEntry> {
+        vec![
+            SaveEntry::GameKey(self.game_key.clone()),
+            SaveEntry::GameProgress(self.game_progress.clone()),
+            SaveEntry::GameRecord(self.game_record.clone()),
+            SaveEntry::Settings(self.settings.clone()),
+            SaveEntry::User(self.user.clone()),
+        ]
+    }
+
+    pub fn version(&self, kind: EntryKind) -> u8 {
+        kind.file_name()
+            .and_then(|name| self.versions.get(name).copied())
+            .unwrap_or_else(|| default_version(kind))
+    }
+
+    /// 写入压缩包时 `kind` 的版本前缀: 读入时记下的前缀, 其次按游戏版本查表,
+    /// 都没有时使用 [`default_version`]
+    pub fn layout_version(&self, kind: EntryKind, versions: &GameVersionTable) -> u8 {
+        kind.file_name()
+            .and_then(|name| self.versions.get(name).copied())
+            .or_else(|| {
+                self.game_version()
+                    .and_then(|version| versions.layout(version, kind))
+            })
+            .unwrap_or_else(|| default_version(kind))
+    }
+
+    /// summary 中的游戏版本, 没有 summary 时为 None
+    pub fn game_version(&self) -> Option<GameVersion> {
+        self.summary.as_ref().map(|s| s.game_version)
+    }
+
+    /// summary 中的游戏版本对应的 rks 规则, 没有 summary 时为当前规则
+    pub fn rks_rule(&self, versions: &GameVersionTable) -> RksRule {
+        self.game_version()
+            .map_or(RksRule::CURRENT, |version| versions.rks_rule(version))
+    }
+
+    /// 按 [`PhiSave::rks_rule`] 计算 rks
+    pub fn rks(&self, table: &DifficultyTable, versions: &GameVersionTable) -> rks::RksResult {
+        rks::compute_with(&self.game_record, table, self.rks_rule(versions))
+    }
+
+    /// 按记录重新统计各难度 clear/fc/phi 数, 给出定数表时按 [`PhiSave::rks`] 重算 rks
+    pub fn rebuild_summary(
+        &self,
+        table: Option<&DifficultyTable>,
+        versions: &GameVersionTable,
+    ) -> SerializableSummary {
+        let prev = self.summary.as_ref();
+        let zero = || SerializableLevel {
+            clear: 0,
+            fc: 0,
+            phi: 0,
+        };
+        let mut level = SerializableMultiLevel {
+            ez: zero(),
+            hd: zero(),
+            r#in: zero(),
+            at: zero(),
+        };
+        for levels in self.game_record.0.values() {
+            for (diff, record) in levels {
+                let Some(count) = level.get_mut(*diff) else {
+                    continue;
+                };
+                let state = record.clear_state();
+                count.clear += state.is_cleared() as u16;
+                count.fc += state.is_full_combo() as u16;
+                count.phi += (state == ClearState::AllPerfect) as u16;
+            }
+        }
+
+        SerializableSummary {
+            save_version: prev.map_or(DEFAULT_SAVE_VERSION, |s| s.save_version),
+            challenge_mode_rank: self.game_progress.challenge_mode_rank,
+            rks: table
+                .map(|t| self.rks(t, versions).rks)
+                .or(prev.map(|s| s.rks))
+                .unwrap_or_default(),
+            game_version: self.game_version().unwrap_or_default(),
+            avatar: self.user.avatar.clone(),
+            level,
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use super::PhiSave;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+/// 单个字段的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactAction {
+    Keep,
+    /// 置为空字符串
+    Clear,
+    /// 每个字符替换为 `*`, 保留字符数
+    Mask,
+    /// 替换为 `<字段>-<hash>`, hash 为以 salt 为密钥的 HMAC-SHA256 的前 8 字节, 同一 salt 下相同的值得到相同的结果
+    Pseudonymize,
+}
+
+/// 脱敏规则, 缺省只处理自由文本字段
+///
+/// avatar / background 对应游戏内资源, 改动后 [`PhiSave::validate`] 可能给出警告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactRules {
+    pub self_intro: RedactAction,
+    pub device_name: RedactAction,
+    pub avatar: RedactAction,
+    pub background: RedactAction,
+    /// 为空时每次脱敏生成随机 salt, 假名只在同一次结果中一致
+    pub salt: String,
+}
+
+impl Default for RedactRules {
+    fn default() -> Self {
+        RedactRules {
+            self_intro: RedactAction::Clear,
+            device_name: RedactAction::Pseudonymize,
+            avatar: RedactAction::Keep,
+            background: RedactAction::Keep,
+            salt: String::new(),
+        }
+    }
+}
+
+/// 一条脱敏记录, 不含原值
+#[derive(Debug, Clone, Serialize)]
+pub struct Redaction {
+    pub field: &'static str,
+    pub action: RedactAction,
+    /// 原值的字符数
+    pub original_len: usize,
+    pub value: String,
+}
+
+/// 由系统随机数生成的 128 位 salt
+fn random_salt() -> Result<String, String> {
+    let mut salt = [0u8; 16];
+    getrandom::getrandom(&mut salt).map_err(|e| format!("Redact error: {}", e))?;
+    Ok(salt.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn apply(
+    field: &'static str,
+    value: &mut String,
+    action: RedactAction,
+    salt: &str,
+    out: &mut Vec<Redaction>,
+) {
+    if value.is_empty() {
+        return;
+    }
+    let redacted = match action {
+        RedactAction::Keep => return,
+        RedactAction::Clear => String::new(),
+        RedactAction::Mask => "*".repeat(value.chars().count()),
+        RedactAction::Pseudonymize => {
+            let mut mac = Hmac::<Sha256>::new_from_slice(salt.as_bytes())
+                .expect("HMAC accepts keys of any length");
+            mac.update(value.as_bytes());
+            let hash = mac.finalize().into_bytes();
+            let hex: String = hash[..8].iter().map(|b| format!("{:02x}", b)).collect();
+            format!("{}-{}", field, hex)
+        }
+    };
+    out.push(Redaction {
+        field,
+        action,
+        original_len: value.chars().count(),
+        value: redacted.clone(),
+    });
+    *value = redacted;
+}
+
+impl PhiSave {
+    /// 按规则就地脱敏, 返回被改动的字段
+    ///
+    /// summary.avatar 与 user.avatar 相同时随之替换
+    pub fn redact(&mut self, rules: &RedactRules) -> Result<Vec<Redaction>, String> {
+        let mut out = Vec::new();
+        let salt = match rules.salt.as_str() {
+            "" => &random_salt()?,
+            salt => salt,
+        };
+        let user = &mut self.user;
+        apply(
+            "user.self_intro",
+            &mut user.self_intro,
+            rules.self_intro,
+            salt,
+            &mut out,
+        );
+        let avatar = user.avatar.clone();
+        apply(
+            "user.avatar",
+            &mut user.avatar,
+            rules.avatar,
+            salt,
+            &mut out,
+        );
+        if let Some(summary) = &mut self.summary
+            && summary.avatar == avatar
+        {
+            summary.avatar = user.avatar.clone();
+        }
+        apply(
+            "user.background",
+            &mut user.background,
+            rules.background,
+            salt,
+            &mut out,
+        );
+        apply(
+            "settings.device_name",
+            &mut self.settings.device_name,
+            rules.device_name,
+            salt,
+            &mut out,
+        );
+        Ok(out)
+    }
+}
@@ -0,0 +1,82 @@
+use super::PhiSave;
+use super::archive::encode_summary;
+use crate::game_version::GameVersionTable;
+use crate::rks::DifficultyTable;
+use crate::summary::serde::SerializableSummary;
+use md5::{Digest, Md5};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 上传所需的全部内容, 可直接交给 `CloudClient::upload`
+#[derive(Debug, Clone)]
+pub struct UploadPayload {
+    pub archive: Vec<u8>,
+    /// 压缩包的 MD5, 写入文件 metaData 的 `_checksum`
+    pub checksum: String,
+    /// base64 编码的 summary, 写入 `_GameSave.summary`
+    pub summary: String,
+    pub summary_data: SerializableSummary,
+    /// 写入 `_GameSave.modifiedAt`
+    pub modified_at: String,
+}
+
+impl PhiSave {
+    /// 重新打包存档并按当前记录生成 summary
+    ///
+    /// 提供定数表时按 summary 中的游戏版本重新计算 rks, 否则沿用已有 summary 中的值,
+    /// 各文件的版本前缀按 [`PhiSave::layout_version`] 选择
+    pub fn prepare_upload(
+        &self,
+        table: Option<&DifficultyTable>,
+        versions: &GameVersionTable,
+    ) -> Result<UploadPayload, String> {
+        let archive = self.to_archive_for(versions)?;
+        let summary_data = self.rebuild_summary(table, versions);
+        Ok(UploadPayload {
+            checksum: checksum(&archive),
+            summary: encode_summary(&summary_data)?,
+            archive,
+            summary_data,
+            modified_at: iso_now(),
+        })
+    }
+}
+
+pub fn checksum(data: &[u8]) -> String {
+    format!("{:x}", Md5::digest(data))
+}
+
+/// ISO 8601 UTC 时间, 精确到毫秒
+pub fn iso_now() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    iso_from_millis(now.as_millis() as i64)
+}
+
+pub fn iso_from_millis(millis: i64) -> String {
+    let secs = millis.div_euclid(1000);
+    let days = secs.div_euclid(86400);
+    let rem = secs.rem_euclid(86400);
+
+    // civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        millis.rem_euclid(1000)
+    )
+}
@@ -0,0 +1,179 @@
+use super::PhiSave;
+use crate::game_key::kind::KeyKind;
+use crate::game_key::serde::SerializableKey;
+use crate::game_record::difficulty::Difficulty;
+use crate::game_record::song_id::SongId;
+use crate::game_version::GameVersionTable;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// 一条检查结果, `check` 为检查项名称
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    pub check: &'static str,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(severity: Severity, check: &'static str, message: String) -> Self {
+        Finding {
+            severity,
+            check,
+            message,
+        }
+    }
+}
+
+impl PhiSave {
+    /// 跨条目一致性检查, 不修改存档
+    pub fn validate(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        self.check_record_keys(&mut findings);
+        self.check_challenge_rank(&mut findings);
+        if let Err(e) = self.game_progress.chapter8() {
+            findings.push(Finding::new(Severity::Error, "chapter8", e));
+        }
+        self.check_summary(&mut findings);
+        self.check_key_refs(&mut findings);
+        findings
+    }
+
+    fn check_record_keys(&self, findings: &mut Vec<Finding>) {
+        for (_, e) in self.game_record.invalid_keys() {
+            findings.push(Finding::new(Severity::Error, "record_key", e));
+        }
+    }
+
+    fn check_challenge_rank(&self, findings: &mut Vec<Finding>) {
+        let ranks = [
+            ("gameProgress", Some(self.game_progress.challenge_mode_rank)),
+            (
+                "summary",
+                self.summary.as_ref().map(|s| s.challenge_mode_rank),
+            ),
+        ];
+        for (entry, rank) in ranks {
+            if let Some(Err(e)) = rank.map(|r| r.validate()) {
+                findings.push(Finding::new(
+                    Severity::Error,
+                    "challenge_mode_rank_tier",
+                    format!("{}: {}", entry, e),
+                ));
+            }
+        }
+    }
+
+    fn check_summary(&self, findings: &mut Vec<Finding>) {
+        let Some(summary) = &self.summary else {
+            findings.push(Finding::new(
+                Severity::Info,
+                "summary_missing",
+                "no summary, summary checks skipped".to_string(),
+            ));
+            return;
+        };
+
+        // 只比较计数, 不重算 rks
+        let rebuilt = self.rebuild_summary(None, &GameVersionTable::default());
+        for diff in Difficulty::RATED {
+            let (Some(have), Some(want)) = (summary.level.get(diff), rebuilt.level.get(diff))
+            else {
+                continue;
+            };
+            let counts = [
+                ("clear", have.clear, want.clear),
+                ("fc", have.fc, want.fc),
+                ("phi", have.phi, want.phi),
+            ];
+            for (name, have, want) in counts {
+                if have != want {
+                    findings.push(Finding::new(
+                        Severity::Warning,
+                        "summary_level",
+                        format!("{} {}: summary {}, record {}", diff, name, have, want),
+                    ));
+                }
+            }
+        }
+
+        if summary.challenge_mode_rank != self.game_progress.challenge_mode_rank {
+            findings.push(Finding::new(
+                Severity::Warning,
+                "challenge_mode_rank",
+                format!(
+                    "summary {}, gameProgress {}",
+                    summary.challenge_mode_rank, self.game_progress.challenge_mode_rank
+                ),
+            ));
+        }
+
+        if summary.avatar != self.user.avatar {
+            findings.push(Finding::new(
+                Severity::Warning,
+                "summary_avatar",
+                format!("summary {:?}, user {:?}", summary.avatar, self.user.avatar),
+            ));
+        }
+    }
+
+    fn find_key(&self, name: &str, kind: KeyKind) -> Option<&SerializableKey> {
+        self.game_key
+            .keys
+            .iter()
+            .find(|k| k.name == name && k.has(kind))
+    }
+
+    /// avatar 与 background 都要有对应类型的 key; background 可以是完整的记录键,
+    /// 此时按曲名查找
+    fn check_key_refs(&self, findings: &mut Vec<Finding>) {
+        let background = self.user.background.as_str();
+        let background_title = background
+            .parse::<SongId>()
+            .map_or(background.to_string(), |id| id.title);
+        let refs = [
+            (
+                "avatar_key",
+                "avatar",
+                self.user.avatar.as_str(),
+                KeyKind::Avatar,
+            ),
+            (
+                "background_key",
+                "background",
+                background_title.as_str(),
+                KeyKind::Illustration,
+            ),
+        ];
+        for (check, field, name, kind) in refs {
+            if !name.is_empty() && self.find_key(name, kind).is_none() {
+                findings.push(Finding::new(
+                    Severity::Warning,
+                    check,
+                    format!("{} {:?} has no {:?} key in gameKey", field, name, kind),
+                ));
+            }
+        }
+
+        // 有单曲解锁 key 但尚未解锁的歌曲不应有成绩
+        for (id, _) in self.game_record.songs() {
+            let locked = self
+                .find_key(&id.title, KeyKind::SingleUnlock)
+                .is_some_and(|k| k.progress(KeyKind::SingleUnlock) == Some(0));
+            if locked {
+                findings.push(Finding::new(
+                    Severity::Warning,
+                    "unlock_key",
+                    format!("{} has records but its unlock key is not set", id),
+                ));
+            }
+        }
+    }
+}
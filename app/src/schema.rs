@@ -0,0 +1,174 @@
+use crate::game_key::serde::SerializableGameKey;
+use crate::game_progress::serde::SerializableGameProgress;
+use crate::game_record::serde::SerializableGameRecord;
+use crate::save::EntryKind;
+use crate::settings::serde::SerializableSettings;
+use crate::summary::serde::SerializableSummary;
+use crate::user::serde::SerializableUser;
+use schemars::{Schema, schema_for};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+pub fn entry_schema(entry: &str) -> Option<Schema> {
+    let schema = match EntryKind::from_name(entry)? {
+        EntryKind::User => schema_for!(SerializableUser),
+        EntryKind::Summary => schema_for!(SerializableSummary),
+        EntryKind::GameRecord => schema_for!(SerializableGameRecord),
+        EntryKind::GameProgress => schema_for!(SerializableGameProgress),
+        EntryKind::GameKey => schema_for!(SerializableGameKey),
+        EntryKind::Settings => schema_for!(SerializableSettings),
+    };
+    Some(schema)
+}
+
+pub fn all_schemas() -> BTreeMap<&'static str, Schema> {
+    EntryKind::ALL
+        .iter()
+        .filter_map(|kind| Some((kind.name(), entry_schema(kind.name())?)))
+        .collect()
+}
+
+/// 去掉 `Serializable` 前缀作为 TypeScript 类型名
+fn ts_name(name: &str) -> String {
+    name.strip_prefix("Serializable")
+        .unwrap_or(name)
+        .to_string()
+}
+
+fn ts_doc(schema: &Value, indent: &str) -> String {
+    match schema.get("description").and_then(Value::as_str) {
+        Some(doc) => format!("{}/** {} */\n", indent, doc.replace('\n', " ")),
+        None => String::new(),
+    }
+}
+
+fn ts_properties(schema: &Value, indent: &str, multiline: bool) -> Vec<String> {
+    let empty = Map::new();
+    let properties = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    // 先按 required 的顺序, 其余按名称
+    let mut names: Vec<&str> = required
+        .iter()
+        .copied()
+        .filter(|n| properties.contains_key(*n))
+        .collect();
+    names.extend(
+        properties
+            .keys()
+            .map(String::as_str)
+            .filter(|n| !required.contains(n)),
+    );
+    names
+        .into_iter()
+        .map(|name| {
+            let prop = &properties[name];
+            let optional = if required.contains(&name) { "" } else { "?" };
+            let doc = if multiline {
+                ts_doc(prop, indent)
+            } else {
+                String::new()
+            };
+            format!("{}{}{}{}: {}", doc, indent, name, optional, ts_type(prop))
+        })
+        .collect()
+}
+
+fn ts_type(schema: &Value) -> String {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return ts_name(reference.rsplit('/').next().unwrap_or(reference));
+    }
+    if let Some(value) = schema.get("const") {
+        return value.to_string();
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return values
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+    if let Some(variants) = schema
+        .get("anyOf")
+        .or_else(|| schema.get("oneOf"))
+        .and_then(Value::as_array)
+    {
+        return variants.iter().map(ts_type).collect::<Vec<_>>().join(" | ");
+    }
+    match schema.get("type") {
+        Some(Value::Array(types)) => types
+            .iter()
+            .map(|t| {
+                let mut single = schema.clone();
+                single["type"] = t.clone();
+                ts_type(&single)
+            })
+            .collect::<Vec<_>>()
+            .join(" | "),
+        Some(Value::String(t)) => match t.as_str() {
+            "string" => "string".to_string(),
+            "integer" | "number" => "number".to_string(),
+            "boolean" => "boolean".to_string(),
+            "null" => "null".to_string(),
+            "array" => {
+                let item = schema.get("items").map(ts_type);
+                match item {
+                    Some(item) if item.contains(' ') => format!("({})[]", item),
+                    Some(item) => format!("{}[]", item),
+                    None => "unknown[]".to_string(),
+                }
+            }
+            "object" => match schema.get("additionalProperties") {
+                Some(value @ Value::Object(_)) => format!("Record<string, {}>", ts_type(value)),
+                _ => format!("{{ {} }}", ts_properties(schema, "", false).join("; ")),
+            },
+            _ => "unknown".to_string(),
+        },
+        _ => "unknown".to_string(),
+    }
+}
+
+/// 由各条目的 JSON Schema 生成 TypeScript 声明, 即 `bind/js/types.d.ts`
+pub fn typescript() -> String {
+    let mut defs: BTreeMap<String, Value> = BTreeMap::new();
+    for (_, schema) in all_schemas() {
+        let mut value = schema.to_value();
+        let Some(root) = value.as_object_mut() else {
+            continue;
+        };
+        if let Some(Value::Object(entries)) = root.remove("$defs") {
+            defs.extend(entries.into_iter().map(|(k, v)| (ts_name(&k), v)));
+        }
+        root.remove("$schema");
+        if let Some(Value::String(title)) = root.remove("title") {
+            defs.insert(ts_name(&title), value);
+        }
+    }
+
+    let mut out = String::from("// 由 phi_save_codec::schema::typescript 生成, 请勿手动修改\n");
+    for (name, schema) in &defs {
+        out.push('\n');
+        out.push_str(&ts_doc(schema, ""));
+        let is_interface = schema.get("type").and_then(Value::as_str) == Some("object")
+            && schema.get("properties").is_some()
+            && !schema
+                .get("additionalProperties")
+                .is_some_and(Value::is_object);
+        if is_interface {
+            out.push_str(&format!("export interface {} {{\n", name));
+            for line in ts_properties(schema, "    ", true) {
+                out.push_str(&format!("{};\n", line));
+            }
+            out.push_str("}\n");
+        } else {
+            out.push_str(&format!("export type {} = {};\n", name, ts_type(schema)));
+        }
+    }
+    out
+}
@@ -1,8 +1,10 @@
 use super::field::{Settings, SettingsBase};
+use super::value::*;
 use crate::phi_base::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SerializableSettingsBase {
     pub chord_support: bool,
     pub fc_ap_indicator: bool,
@@ -10,16 +12,28 @@ pub struct SerializableSettingsBase {
     pub low_resolution_mode: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SerializableSettings {
     pub base: SerializableSettingsBase,
     pub device_name: String,
-    pub bright: f32,
-    pub music_volume: f32,
-    pub effect_volume: f32,
-    pub hit_sound_volume: f32,
-    pub sound_offset: f32,
-    pub note_scale: f32,
+    pub bright: Brightness,
+    pub music_volume: Volume,
+    pub effect_volume: Volume,
+    pub hit_sound_volume: Volume,
+    pub sound_offset: SoundOffset,
+    pub note_scale: NoteScale,
+}
+
+impl SerializableSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        self.bright.validate()?;
+        self.music_volume.validate()?;
+        self.effect_volume.validate()?;
+        self.hit_sound_volume.validate()?;
+        self.sound_offset.validate()?;
+        self.note_scale.validate()
+    }
 }
 
 impl From<SettingsBase> for SerializableSettingsBase {
@@ -49,27 +63,31 @@ impl From<Settings> for SerializableSettings {
         Self {
             base: s.base.into(),
             device_name: s.device_name.0,
-            bright: s.bright,
-            music_volume: s.music_volume,
-            effect_volume: s.effect_volume,
-            hit_sound_volume: s.hit_sound_volume,
-            sound_offset: s.sound_offset,
-            note_scale: s.note_scale,
+            // 保留存档中的原始值, 构建时再检查
+            bright: Brightness::new_unchecked(s.bright),
+            music_volume: Volume::new_unchecked(s.music_volume),
+            effect_volume: Volume::new_unchecked(s.effect_volume),
+            hit_sound_volume: Volume::new_unchecked(s.hit_sound_volume),
+            sound_offset: SoundOffset::from_secs(s.sound_offset),
+            note_scale: NoteScale::new_unchecked(s.note_scale),
         }
     }
 }
 
-impl From<SerializableSettings> for Settings {
-    fn from(s: SerializableSettings) -> Self {
-        Settings {
+impl TryFrom<SerializableSettings> for Settings {
+    type Error = String;
+
+    fn try_from(s: SerializableSettings) -> Result<Self, String> {
+        s.validate()?;
+        Ok(Settings {
             base: s.base.into(),
             device_name: PhiString(s.device_name),
-            bright: s.bright,
-            music_volume: s.music_volume,
-            effect_volume: s.effect_volume,
-            hit_sound_volume: s.hit_sound_volume,
-            sound_offset: s.sound_offset,
-            note_scale: s.note_scale,
-        }
+            bright: s.bright.get(),
+            music_volume: s.music_volume.get(),
+            effect_volume: s.effect_volume.get(),
+            hit_sound_volume: s.hit_sound_volume.get(),
+            sound_offset: s.sound_offset.secs(),
+            note_scale: s.note_scale.get(),
+        })
     }
 }
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// 各项设置的取值范围; 步长只用于 `snapped`, 游戏写入的值可能带有浮点误差, 不据此拒绝.
+// 除 `SoundOffset` 外保存存档中的原始 f32 值, 二进制与 JSON 之间往返不变
+
+macro_rules! ranged {
+    ($(#[$meta:meta])* $name:ident, $what:literal, $min:expr, $max:expr, $step:expr) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+        #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+        #[cfg_attr(feature = "schema", schemars(extend("minimum" = $min, "maximum" = $max)))]
+        #[serde(try_from = "f32", into = "f32")]
+        pub struct $name(f32);
+
+        impl $name {
+            pub const MIN: f32 = $min;
+            pub const MAX: f32 = $max;
+            pub const STEP: f32 = $step;
+
+            pub fn new(value: f32) -> Result<Self, String> {
+                let value = Self::new_unchecked(value);
+                value.validate()?;
+                Ok(value)
+            }
+
+            /// 不检查范围
+            pub fn new_unchecked(value: f32) -> Self {
+                $name(value)
+            }
+
+            pub fn get(self) -> f32 {
+                self.0
+            }
+
+            pub fn validate(self) -> Result<(), String> {
+                let value = self.get();
+                if value.is_finite() && (Self::MIN..=Self::MAX).contains(&value) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "Settings error: {} {} out of range {}..={}",
+                        $what,
+                        value,
+                        Self::MIN,
+                        Self::MAX
+                    ))
+                }
+            }
+
+            /// 对齐到步长并限制在范围内
+            pub fn snapped(self) -> Self {
+                let steps = ((self.get() - Self::MIN) / Self::STEP).round();
+                Self::new_unchecked((Self::MIN + steps * Self::STEP).clamp(Self::MIN, Self::MAX))
+            }
+        }
+
+        impl TryFrom<f32> for $name {
+            type Error = String;
+
+            fn try_from(value: f32) -> Result<Self, String> {
+                $name::new(value)
+            }
+        }
+
+        impl From<$name> for f32 {
+            fn from(value: $name) -> Self {
+                value.get()
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.get())
+            }
+        }
+    };
+}
+
+ranged!(
+    /// 背景亮度
+    Brightness,
+    "bright",
+    0.0,
+    1.0,
+    0.05
+);
+ranged!(
+    /// 音乐, 音效与打击音音量
+    Volume,
+    "volume",
+    0.0,
+    1.0,
+    0.05
+);
+ranged!(NoteScale, "note_scale", 0.5, 2.0, 0.05);
+ranged!(
+    /// 谱面延迟, 单位为毫秒; 存档中以秒保存, 见 [`SoundOffset::from_secs`]
+    SoundOffset,
+    "sound_offset",
+    -1000.0,
+    1000.0,
+    5.0
+);
+
+// 经 f64 换算, 游戏写入的 5 毫秒步长的值往返不变
+impl SoundOffset {
+    /// 不检查范围
+    pub fn from_secs(secs: f32) -> Self {
+        SoundOffset::new_unchecked((secs as f64 * 1000.0) as f32)
+    }
+
+    pub fn secs(self) -> f32 {
+        (self.get() as f64 / 1000.0) as f32
+    }
+}
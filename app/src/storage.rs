@@ -0,0 +1,177 @@
+use crate::game_record::difficulty::Difficulty;
+use crate::game_record::serde::SerializableLevelRecord;
+use crate::game_record::song_id::SongId;
+use crate::game_version::GameVersionTable;
+use crate::rks::DifficultyTable;
+use crate::save::PhiSave;
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// 目录结构:
+//   objects/<hash>.msgpack  存档内容, 以规范化 msgpack 的 md5 命名
+//   players/<player>.json   该玩家的快照列表, 按时间排列
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub hash: String,
+    /// 毫秒时间戳
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SeriesPoint<T> {
+    pub timestamp: i64,
+    pub value: T,
+}
+
+pub struct SnapshotStore {
+    root: PathBuf,
+}
+
+fn io_error(path: &Path, e: impl std::fmt::Display) -> String {
+    format!("Storage error: {}: {}", path.display(), e)
+}
+
+/// msgpack 保留 NaN 等 JSON 无法表示的值, 且 map 均为 BTreeMap, 同一内容编码唯一
+fn canonical(save: &PhiSave) -> Result<Vec<u8>, String> {
+    rmp_serde::to_vec_named(save).map_err(|e| format!("Storage error: {}", e))
+}
+
+fn check_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Storage error: invalid name {:?}", name))
+    }
+}
+
+/// 先写临时文件再改名, 避免中断时留下半个文件
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, bytes).map_err(|e| io_error(&tmp, e))?;
+    fs::rename(&tmp, path).map_err(|e| io_error(path, e))
+}
+
+impl SnapshotStore {
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, String> {
+        let store = SnapshotStore { root: root.into() };
+        for dir in [store.root.join("objects"), store.root.join("players")] {
+            fs::create_dir_all(&dir).map_err(|e| io_error(&dir, e))?;
+        }
+        Ok(store)
+    }
+
+    fn object_path(&self, hash: &str) -> PathBuf {
+        self.root.join("objects").join(format!("{}.msgpack", hash))
+    }
+
+    fn index_path(&self, player: &str) -> PathBuf {
+        self.root.join("players").join(format!("{}.json", player))
+    }
+
+    /// 保存一次快照; 与该玩家最新一次内容相同时不新增记录, 返回原快照
+    pub fn save(&self, player: &str, save: &PhiSave, timestamp: i64) -> Result<Snapshot, String> {
+        check_name(player)?;
+        let bytes = canonical(save)?;
+        let hash = format!("{:x}", Md5::digest(&bytes));
+
+        let object = self.object_path(&hash);
+        if !object.exists() {
+            write_atomic(&object, &bytes)?;
+        }
+
+        let mut snapshots = self.list(player)?;
+        if let Some(last) = snapshots.last().filter(|s| s.hash == hash) {
+            return Ok(last.clone());
+        }
+        let snapshot = Snapshot { hash, timestamp };
+        snapshots.push(snapshot.clone());
+        snapshots.sort_by_key(|s| s.timestamp);
+        let index =
+            serde_json::to_vec_pretty(&snapshots).map_err(|e| format!("Storage error: {}", e))?;
+        write_atomic(&self.index_path(player), &index)?;
+        Ok(snapshot)
+    }
+
+    pub fn load(&self, hash: &str) -> Result<PhiSave, String> {
+        check_name(hash)?;
+        let path = self.object_path(hash);
+        let bytes = fs::read(&path).map_err(|e| io_error(&path, e))?;
+        rmp_serde::from_slice(&bytes).map_err(|e| io_error(&path, e))
+    }
+
+    /// 按时间升序, 没有记录的玩家返回空列表
+    pub fn list(&self, player: &str) -> Result<Vec<Snapshot>, String> {
+        check_name(player)?;
+        let path = self.index_path(player);
+        match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| io_error(&path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(io_error(&path, e)),
+        }
+    }
+
+    pub fn players(&self) -> Result<Vec<String>, String> {
+        let dir = self.root.join("players");
+        let mut players = Vec::new();
+        for entry in fs::read_dir(&dir).map_err(|e| io_error(&dir, e))? {
+            let path = entry.map_err(|e| io_error(&dir, e))?.path();
+            if path.extension().is_some_and(|ext| ext == "json")
+                && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+            {
+                players.push(stem.to_string());
+            }
+        }
+        players.sort();
+        Ok(players)
+    }
+
+    fn series<T>(
+        &self,
+        player: &str,
+        mut value: impl FnMut(&PhiSave) -> Option<T>,
+    ) -> Result<Vec<SeriesPoint<T>>, String> {
+        let mut points = Vec::new();
+        for snapshot in self.list(player)? {
+            let save = self.load(&snapshot.hash)?;
+            if let Some(value) = value(&save) {
+                points.push(SeriesPoint {
+                    timestamp: snapshot.timestamp,
+                    value,
+                });
+            }
+        }
+        Ok(points)
+    }
+
+    /// 给出定数表时按各快照的游戏版本重新计算 rks, 否则使用 summary 中的值
+    pub fn rks_series(
+        &self,
+        player: &str,
+        table: Option<&DifficultyTable>,
+        versions: &GameVersionTable,
+    ) -> Result<Vec<SeriesPoint<f32>>, String> {
+        self.series(player, |save| match table {
+            Some(table) => Some(save.rks(table, versions).rks),
+            None => save.summary.as_ref().map(|s| s.rks),
+        })
+    }
+
+    /// 单个谱面的最佳成绩, 尚未游玩的快照不计入
+    pub fn chart_series(
+        &self,
+        player: &str,
+        song: &SongId,
+        difficulty: Difficulty,
+    ) -> Result<Vec<SeriesPoint<SerializableLevelRecord>>, String> {
+        self.series(player, |save| {
+            save.game_record.get(song)?.get(&difficulty).cloned()
+        })
+    }
+}
@@ -1,3 +1,4 @@
+use crate::game_record::difficulty::Difficulty;
 pub(crate) use crate::phi_base::*;
 use bitvec::prelude::*;
 use shua_struct::field::{BinaryField, Options};
@@ -21,6 +22,19 @@ pub struct MultiLevel {
     pub at: Level,
 }
 
+impl MultiLevel {
+    /// Legacy 不计入 summary, 返回 None
+    pub fn get(&self, difficulty: Difficulty) -> Option<&Level> {
+        match difficulty {
+            Difficulty::Ez => Some(&self.ez),
+            Difficulty::Hd => Some(&self.hd),
+            Difficulty::In => Some(&self.r#in),
+            Difficulty::At => Some(&self.at),
+            Difficulty::Legacy => None,
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 #[binary_struct(bit_order = Lsb0)]
 pub struct Summary {
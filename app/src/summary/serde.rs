@@ -1,15 +1,20 @@
 use super::field::{Level, MultiLevel, Summary};
+use crate::challenge_rank::ChallengeRank;
+use crate::game_record::difficulty::Difficulty;
+use crate::game_version::GameVersion;
 use crate::phi_base::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SerializableLevel {
     pub clear: u16,
     pub fc: u16,
     pub phi: u16,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SerializableMultiLevel {
     pub ez: SerializableLevel,
     pub hd: SerializableLevel,
@@ -18,12 +23,36 @@ pub struct SerializableMultiLevel {
     pub at: SerializableLevel,
 }
 
-#[derive(Serialize, Deserialize)]
+impl SerializableMultiLevel {
+    /// Legacy 不计入 summary, 返回 None
+    pub fn get(&self, difficulty: Difficulty) -> Option<&SerializableLevel> {
+        match difficulty {
+            Difficulty::Ez => Some(&self.ez),
+            Difficulty::Hd => Some(&self.hd),
+            Difficulty::In => Some(&self.r#in),
+            Difficulty::At => Some(&self.at),
+            Difficulty::Legacy => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, difficulty: Difficulty) -> Option<&mut SerializableLevel> {
+        match difficulty {
+            Difficulty::Ez => Some(&mut self.ez),
+            Difficulty::Hd => Some(&mut self.hd),
+            Difficulty::In => Some(&mut self.r#in),
+            Difficulty::At => Some(&mut self.at),
+            Difficulty::Legacy => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SerializableSummary {
     pub save_version: u8,
-    pub challenge_mode_rank: u16,
+    pub challenge_mode_rank: ChallengeRank,
     pub rks: f32,
-    pub game_version: u16,
+    pub game_version: GameVersion,
     pub avatar: String,
     pub level: SerializableMultiLevel,
 }
@@ -74,24 +103,27 @@ impl From<Summary> for SerializableSummary {
     fn from(s: Summary) -> Self {
         Self {
             save_version: s.save_version,
-            challenge_mode_rank: s.challenge_mode_rank,
+            challenge_mode_rank: s.challenge_mode_rank.into(),
             rks: s.rks,
-            game_version: s.game_version.0,
+            game_version: GameVersion(s.game_version.0),
             avatar: s.avatar.0,
             level: s.level.into(),
         }
     }
 }
 
-impl From<SerializableSummary> for Summary {
-    fn from(s: SerializableSummary) -> Self {
-        Summary {
+impl TryFrom<SerializableSummary> for Summary {
+    type Error = String;
+
+    fn try_from(s: SerializableSummary) -> Result<Self, String> {
+        s.challenge_mode_rank.validate()?;
+        Ok(Summary {
             save_version: s.save_version,
-            challenge_mode_rank: s.challenge_mode_rank,
+            challenge_mode_rank: s.challenge_mode_rank.into(),
             rks: s.rks,
-            game_version: VarInt(s.game_version),
+            game_version: VarInt(s.game_version.0),
             avatar: PhiString(s.avatar),
             level: s.level.into(),
-        }
+        })
     }
 }
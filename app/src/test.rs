@@ -23,7 +23,7 @@ pub struct Inventory {
 
 impl Inventory {
     fn get_actual_slots(&self) -> usize {
-        return self.max_slots as usize;
+        self.max_slots as usize
     }
 }
 
@@ -162,3 +162,1034 @@ fn test() {
         panic!()
     }
 }
+
+fn sample_record() -> crate::game_record::serde::SerializableGameRecord {
+    use crate::game_record::difficulty::Difficulty;
+    use crate::game_record::serde::*;
+    let level = |score, acc, fc| SerializableLevelRecord { score, acc, fc };
+    let mut map = std::collections::BTreeMap::new();
+    map.insert(
+        "Glaciaxion.SunsetRay.0".to_string(),
+        [
+            (Difficulty::Ez, level(1_000_000, 100.0, true)),
+            (Difficulty::Hd, level(950_000, 97.5, false)),
+        ]
+        .into(),
+    );
+    map.insert(
+        "Dlyrotz.Likey.0".to_string(),
+        [(Difficulty::In, level(991_900, 99.1, true))].into(),
+    );
+    SerializableGameRecord(map)
+}
+
+#[test]
+fn save_entry_roundtrip() {
+    use crate::save::{EntryKind, SaveEntry};
+
+    let bytes = SaveEntry::GameRecord(sample_record()).build().unwrap();
+    let parsed = SaveEntry::parse(EntryKind::GameRecord, &bytes).unwrap();
+    assert_eq!(parsed.kind(), EntryKind::GameRecord);
+    assert_eq!(parsed.build().unwrap(), bytes);
+}
+
+#[test]
+fn rks_best_and_phi() {
+    use crate::game_version::{GameVersion, GameVersionTable};
+    use crate::rks::{DifficultyTable, chart_rks, compute};
+
+    let table = DifficultyTable::from_tsv(
+        "Glaciaxion.SunsetRay.0\t1.0\t3.5\t10.2\nDlyrotz.Likey.0\t2.0\t6.0\t12.4\n",
+    )
+    .unwrap();
+    assert_eq!(chart_rks(69.9, 15.0), 0.0);
+    assert_eq!(chart_rks(100.0, 15.0), 15.0);
+
+    let result = compute(
+        &sample_record(),
+        &table,
+        &GameVersionTable::default(),
+        GameVersion(0),
+    );
+    assert_eq!(result.best.len(), 3);
+    assert_eq!(result.phi.len(), 1);
+    assert_eq!(result.best[0].song.to_string(), "Dlyrotz.Likey.0");
+    let sum: f32 = result.best.iter().chain(&result.phi).map(|c| c.rks).sum();
+    assert!((result.rks - sum / 30.0).abs() < 1e-6);
+}
+
+fn sample_save() -> crate::save::PhiSave {
+    serde_json::from_value(serde_json::json!({
+        "user": {
+            "show_player_id": true,
+            "self_intro": "hello",
+            "avatar": "Glaciaxion",
+            "background": "Dlyrotz.Likey.0"
+        },
+        "game_record": serde_json::to_value(sample_record()).unwrap(),
+        "game_progress": {
+            "base": {
+                "is_first_run": false,
+                "legacy_chapter_finished": true,
+                "already_show_collection_tip": true,
+                "already_show_auto_unlock_in_tip": true
+            },
+            "completed": "3.0",
+            "song_update_info": 12,
+            "challenge_mode_rank": 312,
+            "money": { "kib": 100, "mib": 3, "gib": 0, "tib": 0, "pib": 0 },
+            "unlock_flag_of_spasmodic": [true, true, false, false],
+            "unlock_flag_of_igallta": [false, false, false, false],
+            "unlock_flag_of_rrharil": [false, false, false, false],
+            "flag_of_song_record_key": [true, false, false, false, false, false, false, false],
+            "random_version_unlocked": [false, false, false, false, false, false],
+            "chapter8_base": { "unlock_begin": true, "unlock_second_phase": false, "passed": false },
+            "chapter8_song_unlocked": [true, false, false, false, false, false],
+            "flag_of_song_record_key_takumi": [false, false, false]
+        },
+        "game_key": {
+            "key_list": [
+                { "name": "Glaciaxion", "type": [false, false, false, false, true], "flag": [true] },
+                { "name": "Dlyrotz", "type": [false, false, false, true, false], "flag": [1] }
+            ],
+            "lanota_read_keys": [false, false, false, false, false, false],
+            "camellia_read_key": [false, false, false, false, false, false, false, false],
+            "side_story4_begin_read_key": false,
+            "old_score_cleared_v390": true
+        },
+        "settings": {
+            "base": {
+                "chord_support": true,
+                "fc_ap_indicator": true,
+                "enable_hit_sound": true,
+                "low_resolution_mode": false
+            },
+            "device_name": "Pixel",
+            "bright": 1.0,
+            "music_volume": 1.0,
+            "effect_volume": 1.0,
+            "hit_sound_volume": 1.0,
+            "sound_offset": 0.0,
+            "note_scale": 1.0
+        },
+        "summary": {
+            "save_version": 6,
+            "challenge_mode_rank": 312,
+            "rks": 0.56,
+            "game_version": 110,
+            "avatar": "Glaciaxion",
+            "level": {
+                "ez": { "clear": 1, "fc": 1, "phi": 1 },
+                "hd": { "clear": 1, "fc": 0, "phi": 0 },
+                "in": { "clear": 1, "fc": 1, "phi": 0 },
+                "at": { "clear": 0, "fc": 0, "phi": 0 }
+            }
+        }
+    }))
+    .unwrap()
+}
+
+#[test]
+fn phi_save_from_entries() {
+    use crate::save::{PhiSave, SaveEntry};
+
+    let save = sample_save();
+    let entries = save.file_entries();
+    assert_eq!(entries.len(), 5);
+    let bytes: Vec<Vec<u8>> = entries.into_iter().map(|e| e.build().unwrap()).collect();
+
+    let rebuilt = PhiSave::from_entries(save.file_entries()).unwrap();
+    assert!(rebuilt.summary.is_none());
+    let rebuilt_bytes: Vec<Vec<u8>> = rebuilt
+        .file_entries()
+        .into_iter()
+        .map(SaveEntry::build)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(bytes, rebuilt_bytes);
+    assert!(PhiSave::from_entries(Vec::new()).is_err());
+}
+
+#[cfg(feature = "cloud-mock")]
+#[test]
+fn cloud_mock_roundtrip() {
+    use crate::cloud::{CloudClient, mock::MockServer};
+    use crate::save::archive::encode_summary;
+
+    let mut save = sample_save();
+    let summary = encode_summary(save.summary.as_ref().unwrap()).unwrap();
+    let server = MockServer::start("session", save.to_archive().unwrap(), &summary).unwrap();
+
+    let denied = CloudClient::with_base_url(&server.base_url, "wrong");
+    assert!(denied.list_saves().is_err());
+
+    let client = CloudClient::with_base_url(&server.base_url, "session");
+    let record = client.list_saves().unwrap().remove(0);
+    let fetched = client.fetch(&record).unwrap();
+    assert_eq!(fetched.user.self_intro, "hello");
+
+    save.user.self_intro = "edited".to_string();
+    let versions = crate::game_version::GameVersionTable::default();
+    let payload = save.prepare_upload(None, &versions).unwrap();
+    assert_eq!(payload.summary_data.rks, 0.56);
+    let table =
+        crate::rks::DifficultyTable::from_tsv("Glaciaxion.SunsetRay.0\t1.0\t3.5\t6.5").unwrap();
+    let fresh = save.prepare_upload(Some(&table), &versions).unwrap();
+    assert_ne!(fresh.summary_data.rks, 0.56);
+    let uploaded = client.upload(&record, &payload).unwrap();
+    assert!(uploaded.warnings.is_empty());
+    let updated = uploaded.save;
+    assert_eq!(updated.summary, payload.summary);
+    assert_eq!(updated.modified_at.iso, payload.modified_at);
+    assert_ne!(updated.game_file.object_id, record.game_file.object_id);
+    assert_eq!(client.fetch(&updated).unwrap().user.self_intro, "edited");
+    assert!(client.download(&record).is_err());
+
+    // 旧文件已不存在时删除失败, 上传仍然成功
+    let uploaded = client.upload(&record, &payload).unwrap();
+    assert_eq!(uploaded.warnings.len(), 1);
+    assert_eq!(client.list_saves().unwrap().len(), 1);
+    assert_eq!(
+        client.fetch(&uploaded.save).unwrap().user.self_intro,
+        "edited"
+    );
+}
+
+#[test]
+fn rebuild_summary_counts() {
+    let save = sample_save();
+    let summary = save.rebuild_summary(None, &Default::default());
+    let level = &summary.level;
+    assert_eq!((level.ez.clear, level.ez.fc, level.ez.phi), (1, 1, 1));
+    assert_eq!((level.hd.clear, level.hd.fc, level.hd.phi), (1, 0, 0));
+    assert_eq!((level.r#in.clear, level.r#in.fc, level.r#in.phi), (1, 1, 0));
+    assert_eq!(level.at.clear, 0);
+    assert_eq!(summary.rks, 0.56);
+    assert_eq!(summary.avatar, save.user.avatar);
+}
+
+#[test]
+fn song_id_roundtrip() {
+    use crate::game_record::song_id::SongId;
+
+    let id: SongId = "Glaciaxion.SunsetRay.0".parse().unwrap();
+    assert_eq!(id, SongId::new("Glaciaxion", "SunsetRay", 0));
+
+    let dotted = "Dead.Soul.Feat.Aoi.5";
+    let id: SongId = dotted.parse().unwrap();
+    assert_eq!(
+        (id.title.as_str(), id.composer.as_str(), id.suffix),
+        ("Dead.Soul.Feat", "Aoi", 5)
+    );
+    assert_eq!(id.to_string(), dotted);
+
+    for bad in [
+        "Glaciaxion",
+        "Glaciaxion.0",
+        "Glaciaxion.SunsetRay.01",
+        ".SunsetRay.0",
+        "Glaciaxion..0",
+        "a.b.x",
+    ] {
+        assert!(bad.parse::<SongId>().is_err(), "{}", bad);
+    }
+
+    let record = sample_record();
+    let id: SongId = "Dlyrotz.Likey.0".parse().unwrap();
+    assert!(
+        record
+            .get(&id)
+            .unwrap()
+            .contains_key(&crate::game_record::difficulty::Difficulty::In)
+    );
+    assert_eq!(record.songs().count(), 2);
+
+    let mut record = record;
+    record.0.insert(
+        "Glaciaxion".to_string(),
+        record.0["Dlyrotz.Likey.0"].clone(),
+    );
+    assert_eq!(record.songs().count(), 2);
+    assert_eq!(record.invalid_keys()[0].0, "Glaciaxion");
+    let anomalies = record.anomalies();
+    assert!(anomalies.iter().all(|a| a.song == "Glaciaxion"
+        && a.flags == [crate::game_record::anomaly::AnomalyFlag::InvalidSongId]));
+    let table = crate::rks::DifficultyTable::from_tsv("Dlyrotz.Likey.0\t1.0\t2.0\t3.0").unwrap();
+    assert_eq!(
+        crate::rks::compute_with(&record, &table, crate::rks::RksRule::CURRENT).skipped,
+        ["Glaciaxion"]
+    );
+}
+
+#[test]
+fn catalog_missing_and_unknown() {
+    use crate::catalog::Catalog;
+    use crate::game_version::GameVersion;
+
+    let catalog = Catalog::from_tsv(
+        GameVersion(110),
+        "Glaciaxion.SunsetRay.0\tGlaciaxion\tSunsetRay\tcatrong\t单曲\tA\tB\tC\n\
+         Credits.Frums.0\tCredits\tFrums\tNaN\t单曲\tD\tE\tF\tG\n",
+    )
+    .unwrap();
+    assert_eq!(catalog.len(), 2);
+    assert!(catalog.covers(GameVersion(110)) && !catalog.covers(GameVersion(111)));
+
+    let credits = catalog.get(&"Credits.Frums.0".parse().unwrap()).unwrap();
+    assert_eq!(
+        credits.charters[&crate::game_record::difficulty::Difficulty::At],
+        "G"
+    );
+
+    let record = sample_record();
+    let missing: Vec<String> = catalog
+        .missing_from(&record)
+        .iter()
+        .map(|s| s.id.to_string())
+        .collect();
+    assert_eq!(missing, ["Credits.Frums.0"]);
+    assert_eq!(catalog.unknown_in(&record), ["Dlyrotz.Likey.0"]);
+
+    let json = serde_json::to_string(&catalog).unwrap();
+    let back: Catalog = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.len(), 2);
+}
+
+#[test]
+fn difficulty_keys_are_checked() {
+    use crate::game_record::{difficulty::Difficulty, field::GameRecord, serde::*};
+
+    let json = r#"{"Glaciaxion.SunsetRay.0":{"Legacy":{"score":1,"acc":1.0,"fc":false},"EZ":{"score":2,"acc":2.0,"fc":true}}}"#;
+    let record: SerializableGameRecord = serde_json::from_str(json).unwrap();
+    let binary = GameRecord::from(record);
+    let song = &binary.song_list[0];
+    assert_eq!(song.unlock, [true, false, false, false, true]);
+    assert_eq!(song.level(Difficulty::Legacy).unwrap().score, 1);
+    assert!(song.level(Difficulty::Hd).is_none());
+
+    let typo = r#"{"Glaciaxion.SunsetRay.0":{"In":{"score":1,"acc":1.0,"fc":false}}}"#;
+    assert!(serde_json::from_str::<SerializableGameRecord>(typo).is_err());
+}
+
+#[test]
+fn grades_and_distribution() {
+    use crate::game_record::{
+        difficulty::Difficulty,
+        grade::{ClearState, Grade},
+    };
+
+    assert_eq!(Grade::from_score(1_000_000, false), Grade::Phi);
+    assert_eq!(Grade::from_score(999_999, true), Grade::VFc);
+    assert_eq!(Grade::from_score(960_000, false), Grade::V);
+    assert_eq!(Grade::from_score(959_999, false), Grade::S);
+    assert_eq!(Grade::from_score(880_000, false), Grade::A);
+    assert_eq!(Grade::from_score(820_000, false), Grade::B);
+    assert_eq!(Grade::from_score(700_000, false), Grade::C);
+    assert_eq!(Grade::from_score(699_999, false), Grade::F);
+    assert_eq!(ClearState::from_score(690_000, true), ClearState::FullCombo);
+    assert_eq!(ClearState::from_score(690_000, false), ClearState::Failed);
+
+    let dist = sample_record().grade_distribution();
+    assert_eq!(dist[&Difficulty::Ez][&Grade::Phi], 1);
+    assert_eq!(dist[&Difficulty::Hd][&Grade::S], 1);
+    assert_eq!(dist[&Difficulty::In][&Grade::VFc], 1);
+    assert!(!dist.contains_key(&Difficulty::At));
+}
+
+#[test]
+fn validate_cross_entry() {
+    use crate::save::Severity;
+
+    let mut save = sample_save();
+    assert!(save.validate().is_empty());
+
+    save.game_progress.challenge_mode_rank = 313.into();
+    save.user.avatar = "Unknown".to_string();
+    save.user.background = "Missing".to_string();
+    save.game_key
+        .keys
+        .push(crate::game_key::serde::SerializableKey::new(
+            "Dlyrotz",
+            &[(crate::game_key::kind::KeyKind::SingleUnlock, 0)].into(),
+        ));
+    save.game_record
+        .0
+        .insert("bad".to_string(), Default::default());
+    let summary = save.summary.as_mut().unwrap();
+    summary.level.hd.clear = 2;
+
+    let findings = save.validate();
+    let checks: Vec<_> = findings.iter().map(|f| f.check).collect();
+    assert_eq!(
+        checks,
+        [
+            "record_key",
+            "summary_level",
+            "challenge_mode_rank",
+            "summary_avatar",
+            "avatar_key",
+            "background_key",
+            "unlock_key"
+        ]
+    );
+    assert_eq!(findings[0].severity, Severity::Error);
+}
+
+#[test]
+fn record_anomalies() {
+    use crate::game_record::{anomaly::AnomalyFlag, difficulty::Difficulty, serde::*};
+
+    let check = AnomalyFlag::check;
+    assert!(check(1_000_000, 100.0, true).is_empty());
+    assert_eq!(check(1_000_000, 100.0, false), [AnomalyFlag::ApWithoutFc]);
+    assert_eq!(check(1_000_001, 100.0, true), [AnomalyFlag::ScoreOverMax]);
+    assert_eq!(check(0, f32::NAN, false), [AnomalyFlag::AccNan]);
+    assert_eq!(
+        check(0, -1.0, true),
+        [AnomalyFlag::AccOutOfRange, AnomalyFlag::FcOnZeroScore]
+    );
+    assert_eq!(check(990_000, 99.1, true), [AnomalyFlag::ScoreAccMismatch]);
+    assert_eq!(check(500_000, 99.0, false), [AnomalyFlag::ScoreAccMismatch]);
+
+    let mut record = sample_record();
+    assert!(record.anomalies().is_empty());
+    record.0.get_mut("Dlyrotz.Likey.0").unwrap().insert(
+        Difficulty::At,
+        SerializableLevelRecord {
+            score: 1_000_000,
+            acc: 98.0,
+            fc: true,
+        },
+    );
+    let anomalies = record.anomalies();
+    assert_eq!(anomalies.len(), 1);
+    assert_eq!(anomalies[0].difficulty, Difficulty::At);
+    assert_eq!(anomalies[0].flags, [AnomalyFlag::ScoreAccMismatch]);
+}
+
+#[test]
+fn challenge_rank_decoding() {
+    use crate::challenge_rank::{ChallengeRank, ChallengeTier};
+    use crate::save::SaveEntry;
+
+    let rank = ChallengeRank(448);
+    assert_eq!(rank.tier(), Some(ChallengeTier::Gold));
+    assert_eq!(rank.level(), 48);
+    assert_eq!(ChallengeRank::new(ChallengeTier::Gold, 48).unwrap(), rank);
+    assert!(ChallengeRank::new(ChallengeTier::Red, 100).is_err());
+    assert_eq!(ChallengeRank(648).tier(), None);
+    assert!(ChallengeRank(648).validate().is_err());
+
+    let raw: ChallengeRank = serde_json::from_str("312").unwrap();
+    let structured: ChallengeRank =
+        serde_json::from_str(r#"{ "tier": "red", "level": 12 }"#).unwrap();
+    assert_eq!(raw, structured);
+    assert!(serde_json::from_str::<ChallengeRank>(r#"{ "tier": "red", "level": 120 }"#).is_err());
+    assert_eq!(serde_json::to_string(&raw).unwrap(), "312");
+    assert_eq!(
+        serde_json::to_string(&raw.structured()).unwrap(),
+        r#"{"tier":"red","level":12}"#
+    );
+    // 越界时保留原始数值, 但不能构建
+    assert_eq!(serde_json::to_string(&ChallengeRank(648)).unwrap(), "648");
+    assert_eq!(ChallengeRank(648).structured(), None);
+
+    let mut save = sample_save();
+    save.game_progress.challenge_mode_rank = ChallengeRank(648);
+    assert!(
+        save.validate()
+            .iter()
+            .any(|f| f.check == "challenge_mode_rank_tier")
+    );
+    assert!(SaveEntry::GameProgress(save.game_progress).build().is_err());
+}
+
+#[test]
+fn money_data_arithmetic() {
+    use crate::game_progress::data::Data;
+    use crate::save::{EntryKind, SaveEntry};
+
+    let money = sample_save().game_progress.money;
+    assert_eq!(money, Data::from_kib(3 * 1024 + 100));
+    assert_eq!(money.to_string(), "3.1 MiB");
+    assert_eq!(Data::from_kib(512).to_string(), "512 KiB");
+    assert_eq!(Data::from_bytes(2048).unwrap(), Data::from_kib(2));
+
+    // 未规范化的输入在输出与构建时进位
+    let mut progress = sample_save().game_progress;
+    progress.money =
+        serde_json::from_str(r#"{ "kib": 2000, "mib": 3, "gib": 0, "tib": 0, "pib": 0 }"#).unwrap();
+    let json = serde_json::to_value(progress.money).unwrap();
+    assert_eq!(
+        (json["kib"].as_u64(), json["mib"].as_u64()),
+        (Some(976), Some(4))
+    );
+    let bytes = SaveEntry::GameProgress(progress.clone()).build().unwrap();
+    let SaveEntry::GameProgress(parsed) =
+        SaveEntry::parse(EntryKind::GameProgress, &bytes).unwrap()
+    else {
+        unreachable!()
+    };
+    assert_eq!(parsed.money, progress.money);
+    progress.money = Data::from_kib(u64::MAX);
+    assert!(SaveEntry::GameProgress(progress).build().is_err());
+
+    let total = Data::from_units([976, 4, 0, 0, 0])
+        .checked_add(Data::from_kib(48))
+        .unwrap();
+    assert_eq!(total.units().unwrap(), [0, 5, 0, 0, 0]);
+    assert_eq!(total.checked_sub(Data::from_kib(5 * 1024 + 1)), None);
+    assert!(Data::from_kib(u64::MAX).units().is_err());
+}
+
+#[test]
+fn merge_saves() {
+    use crate::game_record::{difficulty::Difficulty, serde::SerializableLevelRecord};
+    use crate::save::ChartPolicy;
+
+    let level = |score, acc, fc| SerializableLevelRecord { score, acc, fc };
+    let mut ours = sample_save();
+    let mut theirs = sample_save();
+    assert!(
+        ours.clone()
+            .merge(&theirs, ChartPolicy::Fieldwise)
+            .is_empty()
+    );
+
+    let hd = theirs
+        .game_record
+        .0
+        .get_mut("Glaciaxion.SunsetRay.0")
+        .unwrap();
+    hd.insert(Difficulty::Hd, level(940_000, 98.0, true));
+    hd.insert(Difficulty::In, level(900_000, 95.0, false));
+    theirs.game_key.keys[0].flag = vec![2];
+    theirs.game_progress.unlock_flag_of_igallta.bits[0] = true;
+    theirs
+        .game_record
+        .0
+        .insert("Empty.Nobody.0".into(), Default::default());
+
+    assert_eq!(ChartPolicy::default(), ChartPolicy::BestScore);
+    let mut best = ours.clone();
+    let report = best.merge(&theirs, ChartPolicy::BestScore);
+    let hd = &best.game_record.0["Glaciaxion.SunsetRay.0"][&Difficulty::Hd];
+    assert_eq!((hd.score, hd.acc, hd.fc), (950_000, 97.5, false));
+    assert_eq!(report.charts.len(), 1);
+
+    let report = ours.merge(&theirs, ChartPolicy::Fieldwise);
+    let hd = &ours.game_record.0["Glaciaxion.SunsetRay.0"][&Difficulty::Hd];
+    assert_eq!((hd.score, hd.acc, hd.fc), (950_000, 98.0, true));
+    assert_eq!(report.charts.len(), 2);
+    assert!(report.charts[1].before.is_none());
+    assert_eq!(report.keys, ["Glaciaxion"]);
+    assert_eq!(ours.game_key.keys[0].flag, [2]);
+    assert_eq!(report.flags, ["unlock_flag_of_igallta"]);
+    assert!(!ours.game_record.0.contains_key("Empty.Nobody.0"));
+}
+
+#[cfg(feature = "diff")]
+#[test]
+fn diff_saves() {
+    use crate::game_record::difficulty::Difficulty;
+    use crate::save::diff::{Change, SaveDiff};
+
+    let old = sample_save();
+    let mut new = old.clone();
+    assert!(SaveDiff::between(&old, &new).unwrap().is_empty());
+    // NaN acc 与自身相同
+    let mut nan = old.clone();
+    let level = nan.game_record.0.get_mut("Dlyrotz.Likey.0").unwrap();
+    level.values_mut().for_each(|l| l.acc = f32::NAN);
+    assert!(SaveDiff::between(&nan, &nan.clone()).unwrap().is_empty());
+
+    new.game_record.0.remove("Dlyrotz.Likey.0");
+    let song = new.game_record.0.get_mut("Glaciaxion.SunsetRay.0").unwrap();
+    song.get_mut(&Difficulty::Hd).unwrap().score = 960_000;
+    new.game_key.keys[0].flag = vec![0];
+    new.game_progress.money = crate::game_progress::data::Data::from_kib(3 * 1024 + 200);
+    new.settings.device_name = "iPad".to_string();
+
+    let diff = SaveDiff::between(&old, &new).unwrap();
+    assert!(matches!(diff.changes[0], Change::SongRemoved { .. }));
+    assert!(matches!(diff.changes[1], Change::ChartChanged { .. }));
+    assert!(matches!(diff.changes[2], Change::KeyChanged { .. }));
+    let text = diff.to_string();
+    assert!(text.contains("~ Glaciaxion.SunsetRay.0 HD: 950000 97.50% -> 960000 97.50%"));
+    assert!(text.contains("~ game_progress.money.kib: 100 -> 200"));
+    assert!(text.contains("~ settings.device_name: \"Pixel\" -> \"iPad\""));
+    assert_eq!(diff.changes.len(), 5);
+}
+
+#[cfg(feature = "storage")]
+#[test]
+fn snapshot_store_history() {
+    use crate::game_record::difficulty::Difficulty;
+    use crate::rks::DifficultyTable;
+    use crate::storage::SnapshotStore;
+
+    let root = std::env::temp_dir().join(format!("phi-save-store-{}", std::process::id()));
+    let store = SnapshotStore::open(&root).unwrap();
+
+    let mut save = sample_save();
+    let first = store.save("player1", &save, 1_000).unwrap();
+    // 内容未变时不新增
+    assert_eq!(store.save("player1", &save, 2_000).unwrap(), first);
+
+    let song = "Glaciaxion.SunsetRay.0".parse().unwrap();
+    save.game_record
+        .get_mut(&song)
+        .unwrap()
+        .get_mut(&Difficulty::Hd)
+        .unwrap()
+        .score = 960_000;
+    let second = store.save("player1", &save, 3_000).unwrap();
+    assert_ne!(first.hash, second.hash);
+    assert!(store.save("../x", &save, 0).is_err());
+
+    assert_eq!(store.list("player1").unwrap(), [first.clone(), second]);
+    assert_eq!(store.players().unwrap(), ["player1"]);
+    assert_eq!(
+        store.load(&first.hash).unwrap().game_record.0.len(),
+        save.game_record.0.len()
+    );
+
+    let scores: Vec<u32> = store
+        .chart_series("player1", &song, Difficulty::Hd)
+        .unwrap()
+        .into_iter()
+        .map(|p| p.value.score)
+        .collect();
+    assert_eq!(scores, [950_000, 960_000]);
+    let table = DifficultyTable::from_tsv("Glaciaxion.SunsetRay.0\t1.0\t3.5\t6.5").unwrap();
+    let versions = crate::game_version::GameVersionTable::default();
+    let rks = store
+        .rks_series("player1", Some(&table), &versions)
+        .unwrap();
+    assert_eq!(rks.len(), 2);
+    assert_eq!(
+        store.rks_series("player1", None, &versions).unwrap()[0].value,
+        0.56
+    );
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn push_recommendations() {
+    use crate::game_record::{grade::MAX_SCORE, serde::SerializableLevelRecord};
+    use crate::rks::{DifficultyTable, RksRule, chart_acc, chart_rks, compute_with, recommend};
+
+    let acc = chart_acc(chart_rks(98.5, 12.4), 12.4).unwrap();
+    assert!((acc - 98.5).abs() < 1e-3);
+    assert_eq!(chart_acc(13.0, 12.4), None);
+
+    let table = DifficultyTable::from_tsv(
+        "Glaciaxion.SunsetRay.0\t1.0\t3.5\t10.2\nDlyrotz.Likey.0\t2.0\t6.0\t12.4\n",
+    )
+    .unwrap();
+    let targets = recommend(&sample_record(), &table, RksRule::CURRENT);
+    // Glaciaxion EZ 已 AP, 不再推荐
+    assert_eq!(targets.len(), 5);
+    let first = &targets[0];
+    assert_eq!(first.chart.song.to_string(), "Dlyrotz.Likey.0");
+    assert!((first.gain.unwrap() - 99.652).abs() < 1e-2);
+    assert_eq!(first.enter_best, None);
+    // AP 后单曲 rks 升至定数, 并进入 φ 列表
+    let expected = (12.4 - first.chart.rks + 12.4) / 30.0;
+    assert!((first.ap_gain - expected).abs() < 1e-4);
+    assert!(targets.windows(2).all(|w| w[0].cost() <= w[1].cost()));
+
+    // 与实际 AP 后重新计算的结果一致, best 较少时包括替换最后一名的情况
+    for rule in [
+        RksRule::CURRENT,
+        RksRule::LEGACY,
+        RksRule { best: 2, phi: 1 },
+    ] {
+        let current = compute_with(&sample_record(), &table, rule).rks;
+        for target in recommend(&sample_record(), &table, rule) {
+            let mut record = sample_record();
+            record
+                .0
+                .entry(target.chart.song.to_string())
+                .or_default()
+                .insert(
+                    target.chart.difficulty,
+                    SerializableLevelRecord {
+                        score: MAX_SCORE,
+                        acc: 100.0,
+                        fc: true,
+                    },
+                );
+            let actual = compute_with(&record, &table, rule).rks - current;
+            assert!((target.ap_gain - actual).abs() < 1e-4);
+        }
+    }
+}
+
+#[test]
+fn key_kinds_and_progress() {
+    use crate::game_key::{kind::KeyKind, serde::SerializableKey};
+    use crate::save::{EntryKind, SaveEntry};
+
+    let key: SerializableKey = serde_json::from_str(
+        r#"{ "name": "Lyrith", "type": [true, false, true, false, false], "flag": [1, 3] }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        key.kinds().collect::<Vec<_>>(),
+        [KeyKind::ReadCollection, KeyKind::Collection]
+    );
+    assert_eq!(key.progress(KeyKind::Collection), Some(3));
+    assert_eq!(key.progress(KeyKind::Avatar), None);
+
+    let structured: SerializableKey = serde_json::from_str(
+        r#"{ "name": "Lyrith", "progress": { "read_collection": 1, "collection": 3 } }"#,
+    )
+    .unwrap();
+    assert_eq!(structured, key);
+    assert_eq!(
+        serde_json::to_string(&structured).unwrap(),
+        r#"{"name":"Lyrith","type":[true,false,true,false,false],"flag":[1,3]}"#
+    );
+    assert_eq!(
+        serde_json::to_string(&key.structured()).unwrap(),
+        r#"{"name":"Lyrith","progress":{"read_collection":1,"collection":3}}"#
+    );
+    let mut inconsistent = key.clone();
+    inconsistent.flag.push(0);
+    assert_eq!(inconsistent.structured(), None);
+    // 旧格式的 bool flag
+    let legacy: SerializableKey = serde_json::from_str(
+        r#"{ "name": "Lyrith", "type": [true, false, true, false, false], "flag": [true, false] }"#,
+    )
+    .unwrap();
+    assert_eq!(legacy.flag, [1, 0]);
+
+    // 计数超过 1 时二进制往返不丢失
+    let mut save = sample_save();
+    save.game_key.keys.push(key.clone());
+    let bytes = SaveEntry::GameKey(save.game_key).build().unwrap();
+    let SaveEntry::GameKey(parsed) = SaveEntry::parse(EntryKind::GameKey, &bytes).unwrap() else {
+        unreachable!()
+    };
+    assert_eq!(parsed.keys[2], key);
+
+    // 原始字节: 1 个 key, 名称, 长度 (1 + flag 数), type 位, flag, 其余已读标记
+    let mut raw = vec![0x01, 0x06];
+    raw.extend_from_slice(b"Lyrith");
+    raw.extend_from_slice(&[0x03, 0b00101, 0x01, 0x03, 0x00, 0x00, 0x00, 0x00]);
+    let SaveEntry::GameKey(parsed) = SaveEntry::parse(EntryKind::GameKey, &raw).unwrap() else {
+        unreachable!()
+    };
+    assert_eq!(parsed.keys, std::slice::from_ref(&key));
+    assert_eq!(parsed.keys[0].progress(KeyKind::Collection), Some(3));
+    assert_eq!(SaveEntry::GameKey(parsed).build().unwrap(), raw);
+
+    // length 只有一个字节, 直接构造 (组件构建也经过这里) 与 JSON 都拒绝 255 个 flag
+    let mut long = key.clone();
+    long.ktype = [false; 5];
+    long.flag = vec![1; SerializableKey::MAX_FLAGS];
+    let mut game_key = sample_save().game_key;
+    game_key.keys = vec![long.clone()];
+    assert!(SaveEntry::GameKey(game_key.clone()).build().is_ok());
+    long.flag.push(1);
+    game_key.keys = vec![long.clone()];
+    let err = SaveEntry::GameKey(game_key).build().unwrap_err();
+    assert!(err.contains("too many flags"));
+    let mut json = serde_json::to_value(&long).unwrap();
+    json["flag"] = serde_json::json!(vec![1; 255]);
+    assert!(serde_json::from_value::<SerializableKey>(json).is_err());
+}
+
+#[test]
+fn progress_unlock_flags() {
+    use crate::game_progress::flags::{Chapter8SongFlag, FlagSet, SpasmodicFlag, UnlockTable};
+
+    let mut progress = sample_save().game_progress;
+    assert!(
+        progress
+            .unlock_flag_of_spasmodic
+            .contains(SpasmodicFlag::Step2)
+    );
+    assert!(
+        !progress
+            .unlock_flag_of_spasmodic
+            .contains(SpasmodicFlag::Step3)
+    );
+    progress
+        .chapter8_song_unlocked
+        .set(Chapter8SongFlag::Song3, true);
+    assert_eq!(progress.chapter8_song_unlocked.names(), ["song1", "song3"]);
+
+    let named: FlagSet<SpasmodicFlag, 4> = serde_json::from_str(r#"["step1", "step2"]"#).unwrap();
+    assert_eq!(named, progress.unlock_flag_of_spasmodic);
+    assert!(serde_json::from_str::<FlagSet<SpasmodicFlag, 4>>("[true]").is_err());
+    assert_eq!(
+        serde_json::to_string(&named).unwrap(),
+        r#"["step1","step2"]"#
+    );
+    let empty: FlagSet<SpasmodicFlag, 4> = FlagSet::default();
+    assert_eq!(serde_json::to_string(&empty).unwrap(), "[]");
+    assert_eq!(
+        serde_json::from_str::<FlagSet<SpasmodicFlag, 4>>("[]").unwrap(),
+        empty
+    );
+
+    let table = UnlockTable::from_tsv(
+        "chapter8_song_unlocked\tsong3\tGlaciaxion.SunsetRay.0\nunlock_flag_of_igallta\tstep1\tDlyrotz.Likey.0\n",
+    )
+    .unwrap();
+    let songs = progress.unlocked_songs(&table);
+    assert_eq!(songs.len(), 1);
+    assert_eq!(songs[0].to_string(), "Glaciaxion.SunsetRay.0");
+    assert!(UnlockTable::from_tsv("chapter8_song_unlocked\tsong7\tA.B.0").is_err());
+    // 内置表的每一行都能解析且成员存在
+    assert!(
+        progress
+            .unlocked_songs(&UnlockTable::builtin().unwrap())
+            .iter()
+            .all(|song| !song.to_string().is_empty())
+    );
+}
+
+#[test]
+fn chapter8_progression() {
+    use crate::game_progress::chapter8::{Chapter8Progress, Chapter8Stage};
+    use crate::game_progress::flags::{Chapter8SongFlag, FlagSet, UnlockFlag};
+
+    let mut progress = sample_save().game_progress;
+    let ch8 = progress.chapter8().unwrap();
+    assert_eq!(ch8.stage(), Chapter8Stage::FirstPhase);
+    assert_eq!(ch8.song_count(), 1);
+
+    assert!(ch8.pass().is_err());
+    assert!(ch8.begin().is_err());
+    assert!(ch8.unlock_song(Chapter8SongFlag::Song1).is_err());
+    // 歌曲可以不按顺序解锁
+    let mut next = ch8
+        .unlock_song(Chapter8SongFlag::Song5)
+        .unwrap()
+        .enter_second_phase()
+        .unwrap();
+    for song in Chapter8SongFlag::ALL {
+        if !next.song_flags().contains(*song) {
+            next = next.unlock_song(*song).unwrap();
+        }
+    }
+    let passed = next.pass().unwrap();
+    progress.set_chapter8(passed);
+    assert!(progress.chapter8_base.passed && progress.chapter8_base.unlock_second_phase);
+    assert_eq!(progress.chapter8().unwrap(), passed);
+    assert!(passed.unlock_song(Chapter8SongFlag::Song2).is_err());
+
+    assert!(Chapter8Progress::new(Chapter8Stage::Locked, FlagSet::new([true; 6])).is_err());
+    assert!(Chapter8Progress::new(Chapter8Stage::Passed, FlagSet::new([false; 6])).is_err());
+
+    progress.set_chapter8(Chapter8Progress::LOCKED);
+    progress.chapter8_base.passed = true;
+    assert!(progress.chapter8().is_err());
+    progress.chapter8_base.passed = false;
+    progress.chapter8_song_unlocked.bits[2] = true;
+    assert!(progress.chapter8().is_err());
+
+    let mut save = sample_save();
+    save.game_progress.chapter8_base.passed = true;
+    assert!(save.validate().iter().any(|f| f.check == "chapter8"));
+}
+
+#[test]
+fn game_version_table() {
+    use crate::game_version::{GameVersion, GameVersionTable};
+    use crate::rks::{DifficultyTable, RksRule, compute_with};
+    use crate::save::{EntryKind, default_version};
+
+    let builtin = GameVersionTable::builtin().unwrap();
+    assert!(builtin.releases().all(|r| r.semver().is_ok()));
+    assert!(GameVersionTable::from_tsv("30\t3.x.0\t2023-01-01").is_err());
+    assert!(GameVersionTable::from_tsv("30\t3.0\t2023-01-01").is_err());
+    assert!(GameVersionTable::from_tsv("30\t3.0.0\t2023-01-01\t\tgameKey").is_err());
+    assert!(GameVersionTable::from_tsv("30\t3.0.0\t2023-01-01\t\tsummary=1").is_err());
+
+    let mut table = GameVersionTable::from_tsv(
+        "# 注释\n20\t2.4.0\t2022-01-01\t5\tgameKey=2,gameProgress=3\n40\t3.0.0\t2023-01-01\t9\tgameKey=3\n",
+    )
+    .unwrap();
+    let release = table.release(GameVersion(45)).unwrap();
+    assert_eq!(release.version, "3.0.0");
+    assert_eq!(release.date, "2023-01-01");
+    assert_eq!(table.release(GameVersion(39)).unwrap().version, "2.4.0");
+    assert_eq!(table.release(GameVersion(10)), None);
+    assert_eq!(table.by_song_update(6).unwrap().code, GameVersion(20));
+    assert_eq!(table.rks_rule(GameVersion(25)), RksRule::LEGACY);
+    assert_eq!(table.rks_rule(GameVersion(40)), RksRule::CURRENT);
+
+    table
+        .extend_tsv("40\t3.0.1\t2023-01-02\t9\tgameKey=3")
+        .unwrap();
+    assert_eq!(table.release(GameVersion(40)).unwrap().version, "3.0.1");
+    assert!(table.extend_tsv("x\t3.0.0\t2023-01-01").is_err());
+
+    let difficulty = DifficultyTable::from_tsv(
+        "Glaciaxion.SunsetRay.0\t1.0\t3.5\t10.2\nDlyrotz.Likey.0\t2.0\t6.0\t12.4\n",
+    )
+    .unwrap();
+    let legacy = compute_with(&sample_record(), &difficulty, RksRule::LEGACY);
+    assert_eq!(legacy.phi.len(), 1);
+    let sum: f32 = legacy.best.iter().chain(&legacy.phi).map(|c| c.rks).sum();
+    assert!((legacy.rks - sum / 20.0).abs() < 1e-6);
+
+    // 按 summary 中的游戏版本选择规则
+    let mut save = sample_save();
+    save.summary.as_mut().unwrap().game_version = GameVersion(25);
+    assert_eq!(save.rks_rule(&table), RksRule::LEGACY);
+    assert_eq!(save.rks(&difficulty, &table).rks, legacy.rks);
+    let summary = save.rebuild_summary(Some(&difficulty), &table);
+    assert_eq!(summary.rks, legacy.rks);
+    assert_eq!(summary.game_version, GameVersion(25));
+    save.summary.as_mut().unwrap().game_version = GameVersion(45);
+    assert_ne!(save.rks(&difficulty, &table).rks, legacy.rks);
+
+    // 按游戏版本选择文件版本前缀, 读入时记下的前缀优先
+    assert_eq!(save.layout_version(EntryKind::GameKey, &table), 3);
+    assert_eq!(save.layout_version(EntryKind::GameProgress, &table), 3);
+    assert_eq!(
+        save.layout_version(EntryKind::Settings, &table),
+        default_version(EntryKind::Settings)
+    );
+    save.summary.as_mut().unwrap().game_version = GameVersion(25);
+    assert_eq!(save.layout_version(EntryKind::GameKey, &table), 2);
+    save.versions.insert("gameKey".to_string(), 1);
+    assert_eq!(save.layout_version(EntryKind::GameKey, &table), 1);
+    save.summary = None;
+    assert_eq!(save.layout_version(EntryKind::GameProgress, &table), 4);
+}
+
+#[test]
+fn settings_validation() {
+    use crate::save::SaveEntry;
+    use crate::settings::value::{SoundOffset, Volume};
+
+    assert!(Volume::new(0.5).is_ok());
+    assert!(Volume::new(5.0).is_err());
+    assert!(Volume::new(f32::NAN).is_err());
+    assert_eq!(Volume::new(0.62).unwrap().snapped().get(), 0.6);
+    assert_eq!(SoundOffset::from_secs(-0.12).get(), -120.0);
+    assert_eq!(SoundOffset::new(250.0).unwrap().secs(), 0.25);
+    assert!(SoundOffset::new(1500.0).is_err());
+    // 5 毫秒步长的值与存档中的秒往返不变
+    for ms in (-200..=200).map(|n| n as f32 * 5.0) {
+        let secs = SoundOffset::new(ms).unwrap().secs();
+        assert_eq!(SoundOffset::from_secs(secs).get(), ms);
+        assert_eq!(
+            SoundOffset::from_secs(secs).secs().to_bits(),
+            secs.to_bits()
+        );
+    }
+
+    let mut settings = sample_save().settings;
+    settings.sound_offset = SoundOffset::new(50.0).unwrap();
+    let bytes = SaveEntry::Settings(settings.clone()).build().unwrap();
+    let parsed = SaveEntry::parse(crate::save::EntryKind::Settings, &bytes).unwrap();
+    let SaveEntry::Settings(parsed) = parsed else {
+        unreachable!()
+    };
+    assert_eq!(parsed.sound_offset, settings.sound_offset);
+    let json = serde_json::to_string(&parsed).unwrap();
+    assert!(json.contains(r#""sound_offset":50.0"#));
+
+    // 反序列化时拒绝越界值
+    let mut json = serde_json::to_value(&settings).unwrap();
+    json["music_volume"] = serde_json::json!(5.0);
+    assert!(serde_json::from_value::<crate::settings::serde::SerializableSettings>(json).is_err());
+    // 直接构造的越界值在构建时报错
+    settings.music_volume = Volume::new_unchecked(5.0);
+    let err = SaveEntry::Settings(settings).build().unwrap_err();
+    assert!(err.contains("out of range"));
+}
+
+#[test]
+fn c_exports_match_c_api() {
+    use crate::abi::{C_FUNCTIONS, c_exports};
+    use crate::save::EntryKind;
+
+    let names: Vec<String> = c_exports().into_iter().map(|e| e.name).collect();
+    for kind in EntryKind::ALL {
+        assert!(names.contains(&format!("parse_{}", kind.name())));
+        assert!(names.contains(&format!("build_{}", kind.name())));
+    }
+    // c_api 中手写的 extern 函数都应出现在 C_FUNCTIONS 中
+    let source = include_str!("c_api.rs");
+    let defined: Vec<&str> = source
+        .split("extern \"C\" fn ")
+        .skip(1)
+        .filter_map(|rest| rest.split('(').next())
+        .filter(|name| !name.starts_with('$'))
+        .collect();
+    assert_eq!(defined, C_FUNCTIONS);
+}
+
+#[test]
+fn redact_save() {
+    use crate::save::{RedactAction, RedactRules, SaveEntry};
+
+    let mut save = sample_save();
+    let rules = RedactRules {
+        avatar: RedactAction::Pseudonymize,
+        ..Default::default()
+    };
+    // 未给出 salt 时每次结果不同
+    assert_ne!(
+        save.clone().redact(&rules).unwrap()[2].value,
+        save.clone().redact(&rules).unwrap()[2].value
+    );
+    let salted = RedactRules {
+        salt: "x".into(),
+        ..rules.clone()
+    };
+    let record = save.clone().redact(&salted).unwrap();
+    assert_eq!(
+        save.clone().redact(&salted).unwrap()[2].value,
+        record[2].value
+    );
+    // HMAC-SHA256("x", "Pixel") 的前 8 字节
+    assert_eq!(record[2].value, "settings.device_name-26dd4e6bdbd5f2c8");
+    let other = RedactRules {
+        salt: "y".into(),
+        ..rules.clone()
+    };
+    assert_ne!(
+        save.clone().redact(&other).unwrap()[2].value,
+        record[2].value
+    );
+
+    let record = save.redact(&rules).unwrap();
+    let fields: Vec<_> = record.iter().map(|r| r.field).collect();
+    assert_eq!(
+        fields,
+        ["user.self_intro", "user.avatar", "settings.device_name"]
+    );
+    assert_eq!(record[0].original_len, 5);
+    assert!(save.user.self_intro.is_empty());
+    assert_eq!(save.summary.as_ref().unwrap().avatar, save.user.avatar);
+    assert!(
+        save.settings
+            .device_name
+            .starts_with("settings.device_name-")
+    );
+    assert!(!serde_json::to_string(&record).unwrap().contains("Pixel"));
+
+    let mask = RedactRules {
+        self_intro: RedactAction::Mask,
+        device_name: RedactAction::Keep,
+        ..Default::default()
+    };
+    let mut save = sample_save();
+    assert_eq!(save.redact(&mask).unwrap().len(), 1);
+    assert_eq!(save.user.self_intro, "*****");
+    assert_eq!(save.settings.device_name, "Pixel");
+    for entry in save.file_entries() {
+        let kind = entry.kind();
+        SaveEntry::parse(kind, &entry.build().unwrap()).unwrap();
+    }
+}
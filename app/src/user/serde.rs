@@ -1,7 +1,8 @@
 use super::field::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SerializableUser {
     pub show_player_id: bool,
     pub self_intro: String,
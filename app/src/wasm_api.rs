@@ -0,0 +1,109 @@
+use crate::game_key::{field::GameKey, serde::SerializableGameKey};
+use crate::game_progress::{field::GameProgress, serde::SerializableGameProgress};
+use crate::game_record::{field::GameRecord, serde::SerializableGameRecord};
+use crate::schema::entry_schema;
+use crate::settings::{field::Settings, serde::SerializableSettings};
+use crate::summary::{field::Summary, serde::SerializableSummary};
+use crate::user::{field::User, serde::SerializableUser};
+use bitvec::prelude::*;
+use serde::Serialize;
+use shua_struct::field::BinaryField;
+use wasm_bindgen::prelude::*;
+
+// 由 `phi-save schema --ts` 生成
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = include_str!("../../bind/js/types.d.ts");
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    // 输出普通对象而不是 Map
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    value
+        .serialize(&serializer)
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+#[wasm_bindgen]
+pub fn schema(entry: &str) -> Result<JsValue, JsError> {
+    let schema =
+        entry_schema(entry).ok_or_else(|| JsError::new(&format!("unknown entry: {}", entry)))?;
+    to_js(&schema)
+}
+
+macro_rules! impl_wasm_api {
+    ($struct_ty:ty, $serializable_ty:ty, $parse_fn:ident, $build_fn:ident, $js_parse:literal, $js_build:literal, $ts_ty:literal) => {
+        #[wasm_bindgen(js_name = $js_parse, unchecked_return_type = $ts_ty)]
+        pub fn $parse_fn(data: &[u8]) -> Result<JsValue, JsError> {
+            let bits = BitSlice::<u8, Lsb0>::from_slice(data);
+            let (item, _) = <$struct_ty>::parse(bits, &None).map_err(|e| JsError::new(&e))?;
+            to_js(&<$serializable_ty>::from(item))
+        }
+
+        #[wasm_bindgen(js_name = $js_build)]
+        pub fn $build_fn(
+            #[wasm_bindgen(unchecked_param_type = $ts_ty)] value: JsValue,
+        ) -> Result<Vec<u8>, JsError> {
+            let serializable: $serializable_ty =
+                serde_wasm_bindgen::from_value(value).map_err(|e| JsError::new(&e.to_string()))?;
+            let bitvec = <$struct_ty>::try_from(serializable)
+                .map_err(|e| JsError::new(&e.to_string()))?
+                .build(&None)
+                .map_err(|e| JsError::new(&e))?;
+            Ok(bitvec.into_vec())
+        }
+    };
+}
+
+impl_wasm_api!(
+    User,
+    SerializableUser,
+    parse_user,
+    build_user,
+    "parseUser",
+    "buildUser",
+    "User"
+);
+impl_wasm_api!(
+    Summary,
+    SerializableSummary,
+    parse_summary,
+    build_summary,
+    "parseSummary",
+    "buildSummary",
+    "Summary"
+);
+impl_wasm_api!(
+    GameRecord,
+    SerializableGameRecord,
+    parse_game_record,
+    build_game_record,
+    "parseGameRecord",
+    "buildGameRecord",
+    "GameRecord"
+);
+impl_wasm_api!(
+    GameProgress,
+    SerializableGameProgress,
+    parse_game_progress,
+    build_game_progress,
+    "parseGameProgress",
+    "buildGameProgress",
+    "GameProgress"
+);
+impl_wasm_api!(
+    GameKey,
+    SerializableGameKey,
+    parse_game_key,
+    build_game_key,
+    "parseGameKey",
+    "buildGameKey",
+    "GameKey"
+);
+impl_wasm_api!(
+    Settings,
+    SerializableSettings,
+    parse_settings,
+    build_settings,
+    "parseSettings",
+    "buildSettings",
+    "Settings"
+);
@@ -0,0 +1,167 @@
+// 由 phi_save_codec::schema::typescript 生成, 请勿手动修改
+
+export interface Base {
+    is_first_run: boolean;
+    legacy_chapter_finished: boolean;
+    already_show_collection_tip: boolean;
+    already_show_auto_unlock_in_tip: boolean;
+}
+
+/** 背景亮度 */
+export type Brightness = number;
+
+export type ChallengeRank = number | StructuredRank;
+
+export type ChallengeTier = "none" | "green" | "blue" | "red" | "gold" | "rainbow";
+
+export interface Chapter8Base {
+    unlock_begin: boolean;
+    unlock_second_phase: boolean;
+    passed: boolean;
+}
+
+/** 第八章的歌曲 */
+export type Chapter8SongFlag = "song1" | "song2" | "song3" | "song4" | "song5" | "song6";
+
+export type FlagSet_Chapter8SongFlag = boolean[] | Chapter8SongFlag[];
+
+export type FlagSet_IgalltaFlag = boolean[] | IgalltaFlag[];
+
+export type FlagSet_RandomVersionFlag = boolean[] | RandomVersionFlag[];
+
+export type FlagSet_RrharilFlag = boolean[] | RrharilFlag[];
+
+export type FlagSet_SongRecordKeyFlag = boolean[] | SongRecordKeyFlag[];
+
+export type FlagSet_SpasmodicFlag = boolean[] | SpasmodicFlag[];
+
+export type FlagSet_TakumiKeyFlag = boolean[] | TakumiKeyFlag[];
+
+export interface GameKey {
+    key_list: Key[];
+    lanota_read_keys: boolean[];
+    camellia_read_key: boolean[];
+    side_story4_begin_read_key: boolean;
+    old_score_cleared_v390: boolean;
+}
+
+export interface GameProgress {
+    base: Base;
+    completed: string;
+    song_update_info: number;
+    challenge_mode_rank: ChallengeRank;
+    money: Money;
+    unlock_flag_of_spasmodic: FlagSet_SpasmodicFlag;
+    unlock_flag_of_igallta: FlagSet_IgalltaFlag;
+    unlock_flag_of_rrharil: FlagSet_RrharilFlag;
+    flag_of_song_record_key: FlagSet_SongRecordKeyFlag;
+    random_version_unlocked: FlagSet_RandomVersionFlag;
+    chapter8_base: Chapter8Base;
+    chapter8_song_unlocked: FlagSet_Chapter8SongFlag;
+    flag_of_song_record_key_takumi: FlagSet_TakumiKeyFlag;
+}
+
+export type GameRecord = Record<string, { AT?: LevelRecord; EZ?: LevelRecord; HD?: LevelRecord; IN?: LevelRecord; Legacy?: LevelRecord }>;
+
+/** `Summary.game_version` 中的内部版本号 */
+export type GameVersion = number;
+
+/** Igallta 的解锁步骤 */
+export type IgalltaFlag = "step1" | "step2" | "step3" | "step4";
+
+export type Key = { name: string; type: boolean[]; flag: number[] } | StructuredKey;
+
+export interface Level {
+    clear: number;
+    fc: number;
+    phi: number;
+}
+
+export interface LevelRecord {
+    score: number;
+    acc: number;
+    fc: boolean;
+}
+
+/** [`Data`] 在 JSON 中的形式, 序列化时已规范化 */
+export interface Money {
+    kib: number;
+    mib: number;
+    gib: number;
+    tib: number;
+    pib: number;
+}
+
+export interface MultiLevel {
+    ez: Level;
+    hd: Level;
+    in: Level;
+    at: Level;
+}
+
+export type NoteScale = number;
+
+/** Random 的各个版本 */
+export type RandomVersionFlag = "version1" | "version2" | "version3" | "version4" | "version5" | "version6";
+
+/** Rrhar'il 的解锁步骤 */
+export type RrharilFlag = "step1" | "step2" | "step3" | "step4";
+
+export interface Settings {
+    base: SettingsBase;
+    device_name: string;
+    bright: Brightness;
+    music_volume: Volume;
+    effect_volume: Volume;
+    hit_sound_volume: Volume;
+    sound_offset: SoundOffset;
+    note_scale: NoteScale;
+}
+
+export interface SettingsBase {
+    chord_support: boolean;
+    fc_ap_indicator: boolean;
+    enable_hit_sound: boolean;
+    low_resolution_mode: boolean;
+}
+
+export type SongRecordKeyFlag = "key1" | "key2" | "key3" | "key4" | "key5" | "key6" | "key7" | "key8";
+
+/** 谱面延迟, 单位为毫秒; 存档中以秒保存, 见 [`SoundOffset::from_secs`] */
+export type SoundOffset = number;
+
+/** Spasmodic 的解锁步骤 */
+export type SpasmodicFlag = "step1" | "step2" | "step3" | "step4";
+
+/** 形如 `{ name, progress: { "collection": 2 } }` */
+export interface StructuredKey {
+    name: string;
+    progress: Record<string, number>;
+}
+
+/** 形如 `{ "tier": "gold", "level": 48 }` */
+export interface StructuredRank {
+    tier: ChallengeTier;
+    level: number;
+}
+
+export interface Summary {
+    save_version: number;
+    challenge_mode_rank: ChallengeRank;
+    rks: number;
+    game_version: GameVersion;
+    avatar: string;
+    level: MultiLevel;
+}
+
+export type TakumiKeyFlag = "key1" | "key2" | "key3";
+
+export interface User {
+    show_player_id: boolean;
+    self_intro: string;
+    avatar: string;
+    background: string;
+}
+
+/** 音乐, 音效与打击音音量 */
+export type Volume = number;
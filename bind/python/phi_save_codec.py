@@ -1,3 +1,4 @@
+import json
 import msgpack
 from typing import Any, Callable
 from wasmtime import Store, Module, Instance
@@ -107,6 +108,14 @@ class PhiSaveCodec:
         self._free(out_ptr, out_size)
         return result
 
+    def schema(self, entry: str) -> dict[str, Any]:
+        out = self._call_wasm(self._exports["get_schema"], entry.encode())
+        return json.loads(out)
+
+    def check_game_record(self, data: bytes) -> list[dict[str, Any]]:
+        out = self._call_wasm(self._exports["check_game_record"], data)
+        return Codec.loads(out)
+
     def _call_parser(self, wasm_func, data: bytes) -> dict[str, Any]:
         out = self._call_wasm(wasm_func, data)
         return Codec.loads(out)
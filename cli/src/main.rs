@@ -0,0 +1,142 @@
+use phi_save_codec::game_version::GameVersionTable;
+use phi_save_codec::rks::{DifficultyTable, recommend};
+use phi_save_codec::save::diff::SaveDiff;
+use phi_save_codec::save::{PhiSave, RedactRules};
+use phi_save_codec::schema::{all_schemas, entry_schema, typescript};
+use std::process::exit;
+
+type CliResult = Result<(), Box<dyn std::error::Error>>;
+
+const USAGE: &str = "用法:
+  phi-save schema [entry]          输出条目的 JSON Schema, 省略 entry 时输出全部
+  phi-save schema --ts             输出 TypeScript 声明
+  phi-save validate <save.json>    检查存档各条目是否一致, 输出 JSON 结果
+  phi-save anomalies <save.json>   列出不可能出现的成绩记录
+  phi-save diff <old.json> <new.json> [--json]    比较两份存档
+  phi-save push <save.json> <difficulty.tsv> [count]    推荐提高 rks 的谱面, 默认 10 个
+  phi-save redact <save.json> [rules.json]    脱敏后输出存档, 脱敏记录输出到 stderr";
+
+fn schema(args: &[String]) -> CliResult {
+    let json = match args.first() {
+        Some(flag) if flag == "--ts" => {
+            print!("{}", typescript());
+            return Ok(());
+        }
+        Some(entry) => {
+            let schema = entry_schema(entry).ok_or(format!("未知条目: {}", entry))?;
+            serde_json::to_string_pretty(&schema)?
+        }
+        None => serde_json::to_string_pretty(&all_schemas())?,
+    };
+    println!("{}", json);
+    Ok(())
+}
+
+fn read_save(path: &str) -> Result<PhiSave, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+fn validate(args: &[String]) -> CliResult {
+    let path = args.first().ok_or("缺少存档路径")?;
+    let findings = read_save(path)?.validate();
+    println!("{}", serde_json::to_string_pretty(&findings)?);
+    Ok(())
+}
+
+fn anomalies(args: &[String]) -> CliResult {
+    let path = args.first().ok_or("缺少存档路径")?;
+    let anomalies = read_save(path)?.game_record.anomalies();
+    println!("{}", serde_json::to_string_pretty(&anomalies)?);
+    Ok(())
+}
+
+fn diff(args: &[String]) -> CliResult {
+    let [old, new, rest @ ..] = args else {
+        return Err("缺少存档路径".into());
+    };
+    let diff = SaveDiff::between(&read_save(old)?, &read_save(new)?)?;
+    if rest.iter().any(|a| a == "--json") {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print!("{}", diff);
+    }
+    Ok(())
+}
+
+fn push(args: &[String]) -> CliResult {
+    let [save, table, rest @ ..] = args else {
+        return Err("缺少存档或定数表路径".into());
+    };
+    let count = match rest.first() {
+        Some(n) => n.parse()?,
+        None => 10,
+    };
+    let table = DifficultyTable::from_tsv(&std::fs::read_to_string(table)?)?;
+    let save = read_save(save)?;
+    let rule = save.rks_rule(&GameVersionTable::builtin()?);
+    let targets = recommend(&save.game_record, &table, rule);
+    let targets = &targets[..count.min(targets.len())];
+    println!("{}", serde_json::to_string_pretty(targets)?);
+    Ok(())
+}
+
+fn redact(args: &[String]) -> CliResult {
+    let path = args.first().ok_or("缺少存档路径")?;
+    let rules: RedactRules = match args.get(1) {
+        Some(rules) => serde_json::from_str(&std::fs::read_to_string(rules)?)?,
+        None => RedactRules::default(),
+    };
+    let mut save = read_save(path)?;
+    let record = save.redact(&rules)?;
+    eprintln!("{}", serde_json::to_string_pretty(&record)?);
+    println!("{}", serde_json::to_string_pretty(&save)?);
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("schema") => schema(&args[1..]),
+        Some("validate") => validate(&args[1..]),
+        Some("anomalies") => anomalies(&args[1..]),
+        Some("diff") => diff(&args[1..]),
+        Some("push") => push(&args[1..]),
+        Some("redact") => redact(&args[1..]),
+        _ => {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("错误: {}", e);
+        exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn schema_ranges() {
+        let settings = super::entry_schema("settings").unwrap().to_value();
+        let volume = &settings["$defs"]["Volume"];
+        assert_eq!(
+            (volume["minimum"].as_f64(), volume["maximum"].as_f64()),
+            (Some(0.0), Some(1.0))
+        );
+        let summary = super::entry_schema("summary").unwrap().to_value();
+        let defs = &summary["$defs"];
+        assert_eq!(defs["ChallengeRank"]["anyOf"][0]["maximum"], 599);
+        assert_eq!(defs["StructuredRank"]["properties"]["level"]["maximum"], 99);
+    }
+
+    #[test]
+    fn typescript_declarations_up_to_date() {
+        // 不一致时运行 `cargo run -p cli -- schema --ts > bind/js/types.d.ts`
+        assert_eq!(
+            super::typescript(),
+            include_str!("../../bind/js/types.d.ts")
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use phi_save_codec::game_record::serde::SerializableGameRecord;
+use phi_save_codec::rks::{DifficultyTable, RksRule, compute_with};
+use phi_save_codec::save::{EntryKind, PhiSave, SaveEntry};
+use std::io::Read;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// 请求体默认上限, 超出时返回 413
+const MAX_BODY: u64 = 16 * 1024 * 1024;
+
+const USAGE: &str =
+    "用法: phi-save-server [--addr 127.0.0.1:8080] [--difficulty difficulty.tsv] [--max-body bytes]
+
+  POST /save                  存档压缩包 -> JSON
+  POST /save/build            JSON -> 存档压缩包
+  POST /entry/<entry>         条目二进制 -> JSON
+  POST /entry/<entry>/build   JSON -> 条目二进制
+  POST /rks                   game_record JSON -> 按当前规则计算的 rks (需要 --difficulty)";
+
+enum Body {
+    Json(Vec<u8>),
+    Binary(Vec<u8>),
+}
+
+struct Reply {
+    status: u16,
+    body: Body,
+}
+
+impl Reply {
+    fn json<T: serde::Serialize>(value: &T) -> Result<Self, String> {
+        let bytes = serde_json::to_vec(value).map_err(|e| e.to_string())?;
+        Ok(Reply {
+            status: 200,
+            body: Body::Json(bytes),
+        })
+    }
+
+    fn binary(bytes: Vec<u8>) -> Result<Self, String> {
+        Ok(Reply {
+            status: 200,
+            body: Body::Binary(bytes),
+        })
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        let body = serde_json::json!({ "error": message });
+        Reply {
+            status,
+            body: Body::Json(body.to_string().into_bytes()),
+        }
+    }
+}
+
+struct App {
+    table: Option<DifficultyTable>,
+    max_body: u64,
+}
+
+impl App {
+    fn route(&self, method: &Method, path: &str, body: &[u8]) -> Reply {
+        if *method != Method::Post {
+            return Reply::error(405, "only POST is supported");
+        }
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let result = match segments.as_slice() {
+            ["save"] => PhiSave::from_archive(body).and_then(|save| Reply::json(&save)),
+            ["save", "build"] => serde_json::from_slice::<PhiSave>(body)
+                .map_err(|e| e.to_string())
+                .and_then(|save| save.to_archive())
+                .and_then(Reply::binary),
+            ["entry", name] => entry_kind(name)
+                .and_then(|kind| SaveEntry::parse(kind, body))
+                .and_then(|entry| Reply::json(&entry)),
+            ["entry", name, "build"] => entry_kind(name)
+                .and_then(|kind| {
+                    let mut de = serde_json::Deserializer::from_slice(body);
+                    SaveEntry::deserialize(kind, &mut de).map_err(|e| e.to_string())
+                })
+                .and_then(SaveEntry::build)
+                .and_then(Reply::binary),
+            ["rks"] => self.rks(body),
+            _ => return Reply::error(404, "not found"),
+        };
+        result.unwrap_or_else(|e| Reply::error(400, &e))
+    }
+
+    fn rks(&self, body: &[u8]) -> Result<Reply, String> {
+        let table = self
+            .table
+            .as_ref()
+            .ok_or("difficulty table not loaded, start with --difficulty")?;
+        let record: SerializableGameRecord =
+            serde_json::from_slice(body).map_err(|e| e.to_string())?;
+        // 单独的 game_record 没有游戏版本
+        Reply::json(&compute_with(&record, table, RksRule::CURRENT))
+    }
+}
+
+fn entry_kind(name: &str) -> Result<EntryKind, String> {
+    EntryKind::from_name(name).ok_or_else(|| format!("unknown entry: {}", name))
+}
+
+fn handle(app: &App, mut request: Request) -> std::io::Result<()> {
+    let declared = request.body_length().unwrap_or(0) as u64;
+    let mut body = Vec::new();
+    if declared <= app.max_body {
+        request
+            .as_reader()
+            .take(app.max_body + 1)
+            .read_to_end(&mut body)?;
+    }
+
+    let reply = if declared > app.max_body || body.len() as u64 > app.max_body {
+        Reply::error(413, "request body too large")
+    } else {
+        let path = request
+            .url()
+            .split('?')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        app.route(request.method(), &path, &body)
+    };
+
+    let (content_type, bytes) = match reply.body {
+        Body::Json(b) => ("application/json", b),
+        Body::Binary(b) => ("application/octet-stream", b),
+    };
+    let header = Header::from_bytes("Content-Type", content_type).unwrap();
+    request.respond(
+        Response::from_data(bytes)
+            .with_status_code(reply.status)
+            .with_header(header),
+    )
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut addr = "127.0.0.1:8080".to_string();
+    let mut table = None;
+    let mut max_body = MAX_BODY;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--addr", Some(v)) => addr = v,
+            ("--difficulty", Some(v)) => {
+                table = Some(DifficultyTable::from_tsv(&std::fs::read_to_string(v)?)?)
+            }
+            ("--max-body", Some(v)) => max_body = v.parse()?,
+            _ => {
+                eprintln!("{}", USAGE);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let server = Server::http(&addr).map_err(|e| e.to_string())?;
+    println!("监听: http://{}", addr);
+
+    serve(&server, &App { table, max_body });
+    Ok(())
+}
+
+fn serve(server: &Server, app: &App) {
+    for request in server.incoming_requests() {
+        if let Err(e) = handle(app, request) {
+            eprintln!("响应失败: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpStream;
+    use std::sync::Arc;
+
+    fn start(max_body: u64) -> (Arc<Server>, String) {
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let addr = server.server_addr().to_ip().unwrap().to_string();
+        let handle = Arc::clone(&server);
+        std::thread::spawn(move || {
+            serve(
+                &handle,
+                &App {
+                    table: None,
+                    max_body,
+                },
+            )
+        });
+        (server, addr)
+    }
+
+    fn request(addr: &str, method: &str, path: &str, body: &[u8]) -> (u16, Vec<u8>) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let head = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            method,
+            path,
+            addr,
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).unwrap();
+        stream.write_all(body).unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+
+        let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let status = String::from_utf8_lossy(&response[9..12]).parse().unwrap();
+        (status, response[split + 4..].to_vec())
+    }
+
+    #[test]
+    fn routes() {
+        let (server, addr) = start(1024);
+        let user = br#"{"show_player_id":true,"self_intro":"hi","avatar":"a","background":"b"}"#;
+
+        let (status, bytes) = request(&addr, "POST", "/entry/user/build", user);
+        assert_eq!(status, 200);
+        let (status, json) = request(&addr, "POST", "/entry/user", &bytes);
+        assert_eq!(status, 200);
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value["self_intro"], "hi");
+
+        assert_eq!(request(&addr, "POST", "/entry/nope", b"").0, 400);
+        assert_eq!(request(&addr, "POST", "/missing", b"").0, 404);
+        assert_eq!(request(&addr, "GET", "/save", b"").0, 405);
+        assert_eq!(request(&addr, "POST", "/rks", b"{}").0, 400);
+        assert_eq!(request(&addr, "POST", "/save", &[0; 2048]).0, 413);
+        server.unblock();
+    }
+}
@@ -1,11 +1,62 @@
 use multi_value_gen::parse;
+use phi_save_codec::abi::{AbiExport, AbiType, c_exports};
+use phi_save_codec::save::EntryKind;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
 use std::process::Command;
-use walrus::ValType;
+use walrus::{ExportItem, Module, ValType};
+use wit_component::ComponentEncoder;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+const WASM_FILE: &str = "./target/wasm32-unknown-unknown/release/phi_save_codec.wasm";
+const OUTPUT_DIR: &str = "./output/";
+
+fn val_type(t: AbiType) -> ValType {
+    match t {
+        AbiType::I32 => ValType::I32,
+        AbiType::I64 => ValType::I64,
+        AbiType::F32 => ValType::F32,
+        AbiType::F64 => ValType::F64,
+    }
+}
+
+// 检查清单中的每个导出都存在且签名一致
+fn validate(wasm: &[u8], exports: &[AbiExport]) -> Result<(), Vec<String>> {
+    let module = Module::from_buffer(wasm).map_err(|e| vec![e.to_string()])?;
+    let mut errors = Vec::new();
+
+    for export in exports {
+        let func = module.exports.iter().find_map(|e| match e.item {
+            ExportItem::Function(id) if e.name == export.name => Some(id),
+            _ => None,
+        });
+        let Some(func) = func else {
+            errors.push(format!("{} 不存在", export.name));
+            continue;
+        };
+
+        let ty = module.types.get(module.funcs.get(func).ty());
+        let params: Vec<ValType> = export.params.iter().map(|t| val_type(*t)).collect();
+        let results: Vec<ValType> = export.results.iter().map(|t| val_type(*t)).collect();
+        if ty.params() != params.as_slice() || ty.results() != results.as_slice() {
+            errors.push(format!(
+                "{} 签名不符: 期望 {:?} -> {:?}, 实际 {:?} -> {:?}",
+                export.name,
+                params,
+                results,
+                ty.params(),
+                ty.results()
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn cargo_build(feature: &str) -> Result<(), Box<dyn std::error::Error>> {
     let status = Command::new("cargo")
         .args([
             "build",
@@ -13,7 +64,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             "-p",
             "phi_save_codec",
             "--features",
-            "c_abi",
+            feature,
             "--release",
             "--target",
             "wasm32-unknown-unknown",
@@ -24,54 +75,80 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         eprintln!("cargo build 失败，退出程序");
         std::process::exit(1);
     }
+    Ok(())
+}
 
-    let api_dir = "./app/src/";
-    let mut funcs = HashMap::new();
-
-    if Path::new(api_dir).exists() {
-        for entry in fs::read_dir(api_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            // 只处理目录
-            if path.is_dir() {
-                if let Some(dir_name) = path.file_name() {
-                    let api_name = dir_name.to_string_lossy().to_string();
-                    funcs.insert(
-                        format!("build_{}", api_name),
-                        vec![ValType::I32, ValType::I32],
-                    );
-                    funcs.insert(
-                        format!("parse_{}", api_name),
-                        vec![ValType::I32, ValType::I32],
-                    );
-                }
-            }
-        }
-    } else {
-        println!("警告: 目录 {} 不存在", api_dir);
+// 组件模型: 由 wit-bindgen 嵌入的类型信息直接封装为 component
+fn build_component() -> Result<(), Box<dyn std::error::Error>> {
+    cargo_build("component")?;
+
+    let wasm_bytes = fs::read(WASM_FILE)?;
+    let component = ComponentEncoder::default()
+        .module(&wasm_bytes)?
+        .validate(true)
+        .encode()?;
+
+    fs::create_dir_all(OUTPUT_DIR)?;
+    let output_path = format!("{}phi_save_codec.component.wasm", OUTPUT_DIR);
+    fs::write(&output_path, component)?;
+
+    println!("保存到: {}", output_path);
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::args().any(|arg| arg == "--component") {
+        return build_component();
     }
 
-    println!("找到 {} 个API函数", funcs.len());
+    cargo_build("c_abi")?;
 
-    let wasm_file = "./target/wasm32-unknown-unknown/release/phi_save_codec.wasm";
-    let wasm_bytes = fs::read(wasm_file)?;
+    let exports = c_exports();
+    let funcs: HashMap<String, Vec<ValType>> = exports
+        .iter()
+        .filter(|e| e.multi_value)
+        .map(|e| {
+            (
+                e.name.clone(),
+                e.results.iter().map(|t| val_type(*t)).collect(),
+            )
+        })
+        .collect();
 
-    match parse(wasm_bytes, funcs) {
-        Ok(processed_wasm) => {
-            let output_dir = "./output/";
-            fs::create_dir_all(output_dir)?;
+    println!("找到 {} 个API函数", exports.len());
 
-            let output_path = format!("{}phi_save_codec.wasm", output_dir);
-            fs::write(&output_path, processed_wasm)?;
+    let wasm_bytes = fs::read(WASM_FILE)?;
 
-            println!("保存到: {}", output_path);
-        }
+    let processed_wasm = match parse(wasm_bytes, funcs) {
+        Ok(v) => v,
         Err(e) => {
             eprintln!("处理WASM文件时出错: {}", e);
             return Err(e.into());
         }
+    };
+
+    if let Err(errors) = validate(&processed_wasm, &exports) {
+        for e in &errors {
+            eprintln!("导出校验失败: {}", e);
+        }
+        std::process::exit(1);
     }
 
+    fs::create_dir_all(OUTPUT_DIR)?;
+
+    let output_path = format!("{}phi_save_codec.wasm", OUTPUT_DIR);
+    fs::write(&output_path, processed_wasm)?;
+    println!("保存到: {}", output_path);
+
+    let manifest = serde_json::json!({
+        "module": "phi_save_codec.wasm",
+        "version": phi_save_codec::VERSION,
+        "entries": EntryKind::ALL.map(EntryKind::name),
+        "exports": exports,
+    });
+    let manifest_path = format!("{}phi_save_codec.json", OUTPUT_DIR);
+    fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+    println!("保存到: {}", manifest_path);
+
     Ok(())
 }